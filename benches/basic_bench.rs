@@ -21,15 +21,51 @@ Actually a key[$d]
 [other section]
 k = V  ";
 
+/// Builds a document with many short lines.
+fn short_lines() -> String {
+    (0..100)
+        .map(|section| {
+            let keys: String = (0..20).map(|key| format!("k{key}={key}\n")).collect();
+            format!("[s{section}]\n; c\n{keys}\n")
+        })
+        .collect()
+}
+
+/// Builds a document with long values (such as base64 blobs).
+fn long_lines() -> String {
+    let value: String = (0..2000)
+        .map(|i| {
+            char::from(b"ABCDEFGHIJKLMNOPQRSTUVWXYZabcdefghijklmnopqrstuvwxyz0123456789+/"[i % 64])
+        })
+        .collect();
+    (0..100)
+        .map(|key| format!("key{key} = {value}\n"))
+        .collect()
+}
+
 fn parse(doc: &str) {
     let value: Vec<_> = Parser::new(black_box(doc)).collect();
     black_box(value);
 }
 
+fn iterate(doc: &str) {
+    for item in Parser::new(black_box(doc)) {
+        black_box(item);
+    }
+}
+
 fn criterion_benchmark(c: &mut Criterion) {
     let mut group = c.benchmark_group("ini-parser");
     group.significance_level(0.1).sample_size(30);
     group.bench_function("parse", |b| b.iter(|| parse(black_box(DOCUMENT))));
+    let short = short_lines();
+    group.bench_function("parse short lines", |b| {
+        b.iter(|| iterate(black_box(&short)));
+    });
+    let long = long_lines();
+    group.bench_function("parse long lines", |b| {
+        b.iter(|| iterate(black_box(&long)));
+    });
 }
 
 criterion_group!(benches, criterion_benchmark);
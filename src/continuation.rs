@@ -0,0 +1,85 @@
+//! Multi-line values.
+
+use crate::from_utf8;
+use crate::parse;
+use crate::trim;
+
+/// How property values continue onto following lines.
+///
+/// See [`ParserOptions::continuation`](crate::ParserOptions::continuation).
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Continuation {
+    /// Values never continue (the default).
+    #[default]
+    None,
+    /// Non-blank lines that are indented continue the value of the preceding
+    /// property (as in Python's configparser).
+    ///
+    /// Indented comments and section headers do not continue the value if
+    /// they are enabled in the options.
+    Indented,
+    /// A value ending with `\` continues on the next line.
+    Backslash,
+}
+
+impl Continuation {
+    /// Returns an iterator over the lines of a (possibly multi-line) value.
+    ///
+    /// Each line is trimmed and for [`Continuation::Backslash`] the trailing
+    /// `\` is removed. No allocation is performed.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let options = ini::ParserOptions::new().continuation(ini::Continuation::Backslash);
+    /// let document = "Key = first \\\n  second\nOther = value";
+    ///
+    /// let mut parser = ini::Parser::with_options(document, options);
+    /// let Some(ini::Item::Property{val: Some(val), raw, ..}) = parser.next() else {
+    ///     panic!("expected a property");
+    /// };
+    /// assert_eq!(raw, "Key = first \\\n  second");
+    /// let lines: Vec<_> = ini::Continuation::Backslash.lines(val).collect();
+    /// assert_eq!(lines, ["first", "second"]);
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn lines(self, value: &str) -> ValueLines<'_> {
+        ValueLines {
+            rest: Some(value.as_bytes()),
+            continuation: self,
+        }
+    }
+}
+
+/// Iterator over the lines of a multi-line value.
+///
+/// See [`Continuation::lines`].
+#[derive(Clone, Debug)]
+pub struct ValueLines<'a> {
+    rest: Option<&'a [u8]>,
+    continuation: Continuation,
+}
+
+impl<'a> Iterator for ValueLines<'a> {
+    type Item = &'a str;
+
+    fn next(&mut self) -> Option<&'a str> {
+        let s = self.rest?;
+        let i = parse::find_nl(s);
+        self.rest = match &s[i..] {
+            [] => None,
+            [b'\r', b'\n', rest @ ..] | [b'\r' | b'\n', rest @ ..] => Some(rest),
+            _ => unreachable!("find_nl stops at newlines"),
+        };
+        let mut line = &s[..i];
+        if self.continuation == Continuation::Backslash
+            && let [rest @ .., b'\\'] = line
+        {
+            line = rest;
+        }
+        Some(from_utf8(trim(line)))
+    }
+}
+
+impl core::iter::FusedIterator for ValueLines<'_> {}
@@ -0,0 +1,406 @@
+//! Deserializing ini documents with serde.
+
+use alloc::string::String;
+use alloc::string::ToString;
+use core::fmt;
+
+use serde::de;
+use serde::de::IntoDeserializer;
+use serde::de::Unexpected;
+use serde::de::value::BorrowedStrDeserializer;
+
+use crate::Item;
+use crate::Parser;
+use crate::ParserOptions;
+
+/// Error returned when deserializing fails.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Error {
+    msg: String,
+    line: Option<u32>,
+}
+
+impl Error {
+    /// Returns the line the error occurred on, if known.
+    ///
+    /// The line is zero-based like [`Parser::line`].
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> Option<u32> {
+        self.line
+    }
+
+    /// Sets the line of the error, unless it is already known.
+    fn at_line(mut self, line: u32) -> Self {
+        self.line.get_or_insert(line);
+        self
+    }
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self.line {
+            Some(line) => write!(f, "line {}: {}", line + 1, self.msg),
+            None => f.write_str(&self.msg),
+        }
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl de::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            msg: msg.to_string(),
+            line: None,
+        }
+    }
+}
+
+/// Deserializes an instance of `T` from an ini document.
+///
+/// See [`Deserializer`] for how the document is mapped.
+///
+/// # Errors
+///
+/// Returns an error if the document contains syntax errors or does not match
+/// `T`.
+#[inline]
+pub fn from_str<'de, T: de::Deserialize<'de>>(s: &'de str) -> Result<T, Error> {
+    T::deserialize(&mut Deserializer::new(s))
+}
+
+/// Deserializes an instance of `T` from an ini document using the given
+/// options.
+///
+/// # Errors
+///
+/// Returns an error if the document contains syntax errors or does not match
+/// `T`.
+#[inline]
+pub fn from_str_with_options<'de, T: de::Deserialize<'de>>(
+    s: &'de str,
+    options: ParserOptions,
+) -> Result<T, Error> {
+    T::deserialize(&mut Deserializer::with_options(s, options))
+}
+
+/// A value waiting to be deserialized.
+#[derive(Copy, Clone, Debug)]
+enum Pending<'de> {
+    None,
+    Value(Option<&'de str>),
+    Section,
+}
+
+/// Serde deserializer for ini documents.
+///
+/// The document is deserialized as a map: the global properties (before the
+/// first section) are entries with string values, each section is an entry
+/// whose value is a map of its properties. This maps naturally to a struct
+/// with a field per global property and a nested struct per section:
+///
+/// ```
+/// use ini_roundtrip as ini;
+/// use serde::Deserialize;
+///
+/// #[derive(Deserialize)]
+/// struct Config<'a> {
+///     name: &'a str,
+///     server: Server<'a>,
+/// }
+///
+/// #[derive(Deserialize)]
+/// struct Server<'a> {
+///     host: &'a str,
+///     port: u16,
+///     verbose: bool,
+/// }
+///
+/// let document = "name = example\n[server]\nhost = localhost\nport = 8080\nverbose";
+/// let config: Config<'_> = ini::de::from_str(document).unwrap();
+/// assert_eq!(config.name, "example");
+/// assert_eq!(config.server.host, "localhost");
+/// assert_eq!(config.server.port, 8080);
+/// assert!(config.server.verbose);
+/// ```
+///
+/// Strings are borrowed from the input where possible. Numbers, booleans and
+/// chars are parsed from the value, a property without a value (e.g.
+/// `verbose` above) deserializes as `true` for booleans and as unit. Comments
+/// and blank lines are skipped, while [`Item::Error`] lines are reported as
+/// errors. Errors carry the line they occurred on ([`Error::line`]), errors
+/// concerning a whole section (such as a missing field) the line of its
+/// header. Sections can be optional (`Option<T>` fields).
+///
+/// Requires the `serde` feature.
+#[derive(Clone, Debug)]
+pub struct Deserializer<'de> {
+    parser: Parser<'de>,
+    pending: Pending<'de>,
+    /// Line of the pending property or section header
+    line: u32,
+}
+
+impl<'de> Deserializer<'de> {
+    /// Constructs a new `Deserializer` using the default options.
+    #[inline]
+    #[must_use]
+    pub const fn new(s: &'de str) -> Self {
+        Self::from_parser(Parser::new(s))
+    }
+
+    /// Constructs a new `Deserializer` using the given options.
+    #[inline]
+    #[must_use]
+    pub const fn with_options(s: &'de str, options: ParserOptions) -> Self {
+        Self::from_parser(Parser::with_options(s, options))
+    }
+
+    /// Constructs a new `Deserializer` reading from a parser.
+    #[inline]
+    #[must_use]
+    pub const fn from_parser(parser: Parser<'de>) -> Self {
+        Self {
+            parser,
+            pending: Pending::None,
+            line: 0,
+        }
+    }
+
+    /// Returns the next item that is not a comment or blank line, together
+    /// with its line.
+    fn next_item(&mut self) -> Result<Option<(Item<'de>, u32)>, Error> {
+        loop {
+            let line = self.parser.line();
+            match self.parser.next() {
+                Some(Item::Comment { .. } | Item::Blank { .. }) => {}
+                Some(Item::Error(_)) => {
+                    return Err(
+                        <Error as de::Error>::custom("malformed section header").at_line(line)
+                    );
+                }
+                item => return Ok(item.map(|item| (item, line))),
+            }
+        }
+    }
+
+    /// Deserializes the pending property value.
+    fn value<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        let line = self.line;
+        match core::mem::replace(&mut self.pending, Pending::None) {
+            Pending::Value(val) => seed
+                .deserialize(ValueDeserializer { val })
+                .map_err(|err| err.at_line(line)),
+            Pending::Section => seed
+                .deserialize(SectionDeserializer { de: self })
+                .map_err(|err| err.at_line(line)),
+            Pending::None => Err(de::Error::custom("value requested before key")),
+        }
+    }
+}
+
+impl<'de> de::Deserializer<'de> for &mut Deserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(DocumentAccess { de: self })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+/// Map access over the global properties and sections of a document.
+struct DocumentAccess<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> de::MapAccess<'de> for DocumentAccess<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        loop {
+            let key = match self.de.next_item()? {
+                None => return Ok(None),
+                Some((Item::SectionEnd, _)) => continue,
+                Some((Item::Section { name, .. }, line)) => {
+                    self.de.pending = Pending::Section;
+                    self.de.line = line;
+                    name
+                }
+                Some((Item::Property { key, val, .. }, line)) => {
+                    self.de.pending = Pending::Value(val);
+                    self.de.line = line;
+                    key
+                }
+                Some(_) => unreachable!("comments and errors are handled by next_item"),
+            };
+            return seed
+                .deserialize(BorrowedStrDeserializer::new(key))
+                .map(Some);
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        self.de.value(seed)
+    }
+}
+
+/// Deserializer for the body of a section.
+struct SectionDeserializer<'a, 'de> {
+    de: &'a mut Deserializer<'de>,
+}
+
+impl<'de> de::Deserializer<'de> for SectionDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_map(self)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        // A present section is always `Some`, absent ones are missing fields
+        visitor.visit_some(self)
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf unit unit_struct newtype_struct seq tuple
+        tuple_struct map struct enum identifier ignored_any
+    }
+}
+
+impl<'de> de::MapAccess<'de> for SectionDeserializer<'_, 'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: de::DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        match self.de.next_item()? {
+            None | Some((Item::SectionEnd, _)) => Ok(None),
+            Some((Item::Property { key, val, .. }, line)) => {
+                self.de.pending = Pending::Value(val);
+                self.de.line = line;
+                seed.deserialize(BorrowedStrDeserializer::new(key))
+                    .map(Some)
+            }
+            Some(_) => unreachable!("sections are preceded by a section end"),
+        }
+    }
+
+    fn next_value_seed<V: de::DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        self.de.value(seed)
+    }
+}
+
+/// Deserializer for the value of a property.
+struct ValueDeserializer<'de> {
+    val: Option<&'de str>,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    /// Returns the value, or an error for properties without a value.
+    fn str(&self) -> Result<&'de str, Error> {
+        self.val
+            .ok_or_else(|| de::Error::custom("property has no value"))
+    }
+}
+
+/// Implements deserializing a number by parsing the value.
+macro_rules! deserialize_parse {
+    ($($method:ident => $visit:ident,)*) => {$(
+        fn $method<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+            let s = self.str()?;
+            match s.parse() {
+                Ok(value) => visitor.$visit(value),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(s), &visitor)),
+            }
+        }
+    )*};
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.val {
+            Some(val) => visitor.visit_borrowed_str(val),
+            None => visitor.visit_unit(),
+        }
+    }
+
+    fn deserialize_bool<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.val {
+            None => visitor.visit_bool(true),
+            Some(s) => match s.parse() {
+                Ok(value) => visitor.visit_bool(value),
+                Err(_) => Err(de::Error::invalid_value(Unexpected::Str(s), &visitor)),
+            },
+        }
+    }
+
+    deserialize_parse! {
+        deserialize_i8 => visit_i8,
+        deserialize_i16 => visit_i16,
+        deserialize_i32 => visit_i32,
+        deserialize_i64 => visit_i64,
+        deserialize_i128 => visit_i128,
+        deserialize_u8 => visit_u8,
+        deserialize_u16 => visit_u16,
+        deserialize_u32 => visit_u32,
+        deserialize_u64 => visit_u64,
+        deserialize_u128 => visit_u128,
+        deserialize_f32 => visit_f32,
+        deserialize_f64 => visit_f64,
+        deserialize_char => visit_char,
+    }
+
+    fn deserialize_str<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.str()?)
+    }
+
+    fn deserialize_string<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.str()?.as_bytes())
+    }
+
+    fn deserialize_byte_buf<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_bytes(visitor)
+    }
+
+    fn deserialize_option<V: de::Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_newtype_struct<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_enum<V: de::Visitor<'de>>(
+        self,
+        _name: &'static str,
+        _variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_enum(self.str()?.into_deserializer())
+    }
+
+    serde::forward_to_deserialize_any! {
+        unit unit_struct seq tuple tuple_struct map struct identifier ignored_any
+    }
+}
@@ -0,0 +1,696 @@
+//! Owned, editable INI document.
+
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::ops::Range;
+
+use crate::Continuation;
+use crate::Item;
+use crate::Newline;
+use crate::Parser;
+use crate::ParserOptions;
+use crate::Writer;
+use crate::is_indent;
+use crate::subrange;
+
+/// The kind of a line, with byte ranges into the raw line.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+enum Kind {
+    Error,
+    Section {
+        name: Range<usize>,
+        comment: Option<Range<usize>>,
+    },
+    Property {
+        key: Range<usize>,
+        val: Option<Range<usize>>,
+        comment: Option<Range<usize>>,
+    },
+    Comment,
+    Blank,
+}
+
+/// Returns true if the text contains no newlines.
+fn is_single_line(s: &str) -> bool {
+    !s.contains(['\r', '\n'])
+}
+
+/// A single owned line of the document.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+struct Line {
+    kind: Kind,
+    raw: String,
+    newline: Option<Newline>,
+}
+
+impl Line {
+    fn from_item(item: &Item<'_>, newline: Option<Newline>) -> Option<Self> {
+        let (kind, raw) = match *item {
+            Item::Error(raw) => (Kind::Error, raw),
+            Item::Section { name, comment, raw } => (
+                Kind::Section {
+                    name: subrange(raw, name),
+                    comment: comment.map(|comment| subrange(raw, comment)),
+                },
+                raw,
+            ),
+            Item::SectionEnd => return None,
+            Item::Property {
+                key,
+                val,
+                comment,
+                raw,
+            } => (
+                Kind::Property {
+                    key: subrange(raw, key),
+                    val: val.map(|val| subrange(raw, val)),
+                    comment: comment.map(|comment| subrange(raw, comment)),
+                },
+                raw,
+            ),
+            Item::Comment { raw } => (Kind::Comment, raw),
+            Item::Blank { raw } => (Kind::Blank, raw),
+        };
+        Some(Self {
+            kind,
+            raw: raw.into(),
+            newline,
+        })
+    }
+
+    fn property(key: &str, delimiter: char, value: &str) -> Self {
+        let mut raw = String::with_capacity(key.len() + value.len() + 1);
+        raw.push_str(key);
+        raw.push(delimiter);
+        raw.push_str(value);
+        Self {
+            kind: Kind::Property {
+                key: 0..key.len(),
+                val: Some(key.len() + 1..raw.len()),
+                comment: None,
+            },
+            raw,
+            newline: None,
+        }
+    }
+
+    fn section(name: &str) -> Self {
+        let mut raw = String::with_capacity(name.len() + 2);
+        raw.push('[');
+        raw.push_str(name);
+        raw.push(']');
+        Self {
+            kind: Kind::Section {
+                name: 1..name.len() + 1,
+                comment: None,
+            },
+            raw,
+            newline: None,
+        }
+    }
+
+    fn blank() -> Self {
+        Self {
+            kind: Kind::Blank,
+            raw: String::new(),
+            newline: None,
+        }
+    }
+
+    fn item(&self) -> Item<'_> {
+        let raw = self.raw.as_str();
+        match self.kind {
+            Kind::Error => Item::Error(raw),
+            Kind::Section {
+                ref name,
+                ref comment,
+            } => Item::Section {
+                name: &raw[name.clone()],
+                comment: comment.clone().map(|comment| &raw[comment]),
+                raw,
+            },
+            Kind::Property {
+                ref key,
+                ref val,
+                ref comment,
+            } => Item::Property {
+                key: &raw[key.clone()],
+                val: val.clone().map(|val| &raw[val]),
+                comment: comment.clone().map(|comment| &raw[comment]),
+                raw,
+            },
+            Kind::Comment => Item::Comment { raw },
+            Kind::Blank => Item::Blank { raw },
+        }
+    }
+
+    /// Does this line start a new section (possibly a malformed one)?
+    const fn is_header(&self) -> bool {
+        matches!(self.kind, Kind::Error | Kind::Section { .. })
+    }
+
+    /// Returns the key if this line is a property.
+    fn key(&self) -> Option<&str> {
+        match self.kind {
+            Kind::Property { ref key, .. } => Some(&self.raw[key.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Returns the section name if this line is a well-formed section header.
+    fn section_name(&self) -> Option<&str> {
+        match self.kind {
+            Kind::Section { ref name, .. } => Some(&self.raw[name.clone()]),
+            _ => None,
+        }
+    }
+
+    /// Replace the value of a property, preserving the surrounding formatting
+    /// and any inline comment.
+    fn set_value(&mut self, delimiter: char, value: &str) {
+        let Kind::Property {
+            ref key,
+            ref mut val,
+            ref mut comment,
+        } = self.kind
+        else {
+            unreachable!("set_value called on a non-property line");
+        };
+        let old_len = self.raw.len();
+        match val {
+            Some(val) => {
+                self.raw.replace_range(val.clone(), value);
+                val.end = val.start + value.len();
+            }
+            None => {
+                // Key only, add a delimiter and value right after the key
+                let mut insert = String::with_capacity(value.len() + 1);
+                insert.push(delimiter);
+                insert.push_str(value);
+                self.raw.insert_str(key.end, &insert);
+                *val = Some(key.end + 1..key.end + insert.len());
+            }
+        }
+        if let Some(comment) = comment {
+            // The comment follows the value, shift it accordingly. Strings
+            // are at most `isize::MAX` bytes long, so the lengths fit.
+            let shift = self.raw.len() as isize - old_len as isize;
+            comment.start = comment
+                .start
+                .checked_add_signed(shift)
+                .expect("the comment follows the value");
+            comment.end = comment
+                .end
+                .checked_add_signed(shift)
+                .expect("the comment follows the value");
+        }
+    }
+}
+
+/// Owned, editable INI document.
+///
+/// The document keeps every line that was parsed (including comments, blank
+/// lines and syntax errors) together with the raw text. Lines that are not
+/// modified are written back exactly as they were read.
+///
+/// Sections are identified by name, with `None` referring to the properties
+/// before the first section header. If a section or key occurs multiple times,
+/// lookups operate on the first occurrence.
+///
+/// The newline of every line is kept as well, including a missing newline at
+/// the end of the document. New lines use the first newline found in the
+/// document (see [`newline`](Document::newline)).
+///
+/// ```
+/// use ini_roundtrip::Document;
+///
+/// let mut doc = Document::parse("\
+/// ; Settings
+/// [Section]
+/// Key = Value ; not a comment
+/// ");
+///
+/// assert_eq!(doc.get(Some("Section"), "Key"), Some("Value ; not a comment"));
+///
+/// doc.set(Some("Section"), "Key", "Other");
+/// doc.set(Some("Section"), "New", "Added");
+/// doc.set(Some("Another"), "Key", "Value");
+///
+/// assert_eq!(doc.to_string(), "\
+/// ; Settings
+/// [Section]
+/// Key = Other
+/// New=Added
+///
+/// [Another]
+/// Key=Value
+/// ");
+/// ```
+#[derive(Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Document {
+    lines: Vec<Line>,
+    options: ParserOptions,
+}
+
+impl Document {
+    /// Constructs a new, empty `Document`.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self::with_options(ParserOptions::new())
+    }
+
+    /// Constructs a new, empty `Document` for the given dialect.
+    ///
+    /// New properties use the first of the configured delimiters (or `=` if
+    /// there are none).
+    #[inline]
+    #[must_use]
+    pub const fn with_options(options: ParserOptions) -> Self {
+        Self {
+            lines: Vec::new(),
+            options,
+        }
+    }
+
+    /// Parses a document from a string.
+    #[must_use]
+    pub fn parse(s: &str) -> Self {
+        Self::parse_with_options(s, ParserOptions::new())
+    }
+
+    /// Parses a document from a string using the given dialect.
+    #[must_use]
+    pub fn parse_with_options(s: &str, options: ParserOptions) -> Self {
+        let mut doc = Self::with_options(options);
+        doc.extend(Parser::with_options(s, options).with_newlines());
+        doc
+    }
+
+    /// Returns the options of the document.
+    #[inline]
+    #[must_use]
+    pub const fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Returns the newline used for lines added to the document.
+    ///
+    /// This is the first newline in the document, or [`Newline::Lf`] if there
+    /// is none.
+    #[must_use]
+    pub fn newline(&self) -> Newline {
+        self.lines
+            .iter()
+            .find_map(|line| line.newline)
+            .unwrap_or_default()
+    }
+
+    /// Returns an iterator over the items of the document.
+    ///
+    /// The items are equivalent to what [`Parser`] would produce for the
+    /// written out document, including the [`Item::SectionEnd`]
+    /// pseudo-elements.
+    #[inline]
+    pub fn items(&self) -> Items<'_> {
+        Items {
+            lines: self.lines.iter(),
+            section_ended: false,
+        }
+    }
+
+    /// Returns an iterator over the names of all well-formed sections.
+    pub fn sections(&self) -> impl Iterator<Item = &str> {
+        self.lines.iter().filter_map(Line::section_name)
+    }
+
+    /// Returns true if the given section exists.
+    ///
+    /// The `None` section (properties before the first section header) always
+    /// exists.
+    #[must_use]
+    pub fn contains_section(&self, section: Option<&str>) -> bool {
+        self.bodies(section).next().is_some()
+    }
+
+    /// Returns true if the given property exists in the given section.
+    #[must_use]
+    pub fn contains_key(&self, section: Option<&str>, key: &str) -> bool {
+        self.find(section, key).is_some()
+    }
+
+    /// Returns the trimmed value of a property.
+    ///
+    /// Returns `None` if the property does not exist or has no value (there is
+    /// no `=` on the line).
+    #[must_use]
+    pub fn get(&self, section: Option<&str>, key: &str) -> Option<&str> {
+        match self.lines[self.find(section, key)?].item() {
+            Item::Property { val, .. } => val,
+            _ => None,
+        }
+    }
+
+    /// Sets the value of a property.
+    ///
+    /// An existing property is updated in place, preserving the whitespace
+    /// around the value. Otherwise the property is inserted after the last
+    /// non-blank line of the section, creating the section at the end of the
+    /// document if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key, value or section name would not parse back to the
+    /// same property (see [`insert`](Document::insert)).
+    pub fn set(&mut self, section: Option<&str>, key: &str, value: &str) {
+        self.assert_property(key, value);
+        match self.find(section, key) {
+            Some(index) => {
+                let delimiter = self.delimiter();
+                self.lines[index].set_value(delimiter, value);
+            }
+            None => self.insert(section, key, value),
+        }
+    }
+
+    /// Inserts a new property without checking for an existing one.
+    ///
+    /// The property is inserted after the last non-blank line of the section,
+    /// creating the section at the end of the document if needed.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key or value would not parse back to the same property,
+    /// or if the section has to be created and its name is invalid (see
+    /// [`insert_section`](Document::insert_section)). This is the case if:
+    ///
+    /// * the key or value contains a newline or starts or ends with
+    ///   whitespace,
+    /// * the key contains a delimiter, starts with `[` or a comment prefix,
+    ///   or is empty while the delimiter is whitespace,
+    /// * the key or value contains an inline comment prefix preceded by
+    ///   whitespace (or at the start of the value),
+    /// * the value ends with `\` when using [`Continuation::Backslash`],
+    /// * or the dialect has no delimiters.
+    pub fn insert(&mut self, section: Option<&str>, key: &str, value: &str) {
+        self.assert_property(key, value);
+        let body = self.bodies(section).next();
+        let index = match body {
+            Some(body) => {
+                let mut index = body.end;
+                while index > body.start && matches!(self.lines[index - 1].kind, Kind::Blank) {
+                    index -= 1;
+                }
+                index
+            }
+            None => {
+                // Only named sections can be missing
+                self.insert_section(section.unwrap_or_default());
+                self.lines.len()
+            }
+        };
+        self.insert_line(index, Line::property(key, self.delimiter(), value));
+    }
+
+    /// Appends a new, empty section at the end of the document.
+    ///
+    /// Returns false (and does nothing) if the section already exists.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the name contains `]`, a newline or an inline comment prefix
+    /// preceded by whitespace, or if it starts or ends with whitespace.
+    pub fn insert_section(&mut self, name: &str) -> bool {
+        if let Err(msg) = self.check_section(name) {
+            panic!("invalid section name: {msg}");
+        }
+        if self.contains_section(Some(name)) {
+            return false;
+        }
+        if self
+            .lines
+            .last()
+            .is_some_and(|line| !matches!(line.kind, Kind::Blank))
+        {
+            self.insert_line(self.lines.len(), Line::blank());
+        }
+        self.insert_line(self.lines.len(), Line::section(name));
+        true
+    }
+
+    /// Removes a property from a section.
+    ///
+    /// Returns false if the property did not exist.
+    pub fn remove(&mut self, section: Option<&str>, key: &str) -> bool {
+        match self.find(section, key) {
+            Some(index) => {
+                self.remove_lines(index..index + 1);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Removes a section, including its header and all lines up to the next
+    /// section header.
+    ///
+    /// Removing the `None` section removes all lines before the first section
+    /// header. Returns false if the section did not exist.
+    pub fn remove_section(&mut self, section: Option<&str>) -> bool {
+        let Some(body) = self.bodies(section).next() else {
+            return false;
+        };
+        let start = if section.is_some() {
+            body.start - 1
+        } else {
+            body.start
+        };
+        self.remove_lines(start..body.end);
+        true
+    }
+
+    /// Checks that a property parses back to the same key and value when
+    /// written, returning why not otherwise.
+    pub(crate) fn check_property(&self, key: &str, value: &str) -> Result<(), &'static str> {
+        let options = &self.options;
+        if options.delimiters.is_empty() {
+            return Err("the dialect has no delimiters");
+        }
+        if !is_single_line(key) || !is_single_line(value) {
+            return Err("keys and values must not contain newlines");
+        }
+        if key.trim_ascii() != key || value.trim_ascii() != value {
+            return Err("keys and values must not start or end with whitespace");
+        }
+        if key.bytes().any(|chr| options.delimiters.contains(&chr)) {
+            return Err("keys must not contain delimiters");
+        }
+        match key.as_bytes().first() {
+            Some(b'[') => return Err("keys must not start with `[`"),
+            Some(chr) if options.comment_prefixes.contains(chr) => {
+                return Err("keys must not start with a comment prefix");
+            }
+            None if self.delimiter().is_ascii_whitespace() => {
+                return Err("keys must not be empty if the delimiter is whitespace");
+            }
+            _ => {}
+        }
+        if self.has_inline_comment(key, false) || self.has_inline_comment(value, true) {
+            return Err("keys and values must not contain inline comments");
+        }
+        if options.continuation == Continuation::Backslash && value.ends_with('\\') {
+            return Err("values must not end with a line continuation");
+        }
+        Ok(())
+    }
+
+    /// Checks that a section header parses back to the same name when
+    /// written, returning why not otherwise.
+    pub(crate) fn check_section(&self, name: &str) -> Result<(), &'static str> {
+        if !is_single_line(name) {
+            return Err("section names must not contain newlines");
+        }
+        if name.trim_ascii() != name {
+            return Err("section names must not start or end with whitespace");
+        }
+        if name.contains(']') {
+            return Err("section names must not contain `]`");
+        }
+        if self.has_inline_comment(name, false) {
+            return Err("section names must not contain inline comments");
+        }
+        Ok(())
+    }
+
+    /// Returns true if the text contains an inline comment prefix preceded by
+    /// whitespace, or at its start if `at_start` is set.
+    fn has_inline_comment(&self, s: &str, at_start: bool) -> bool {
+        let bytes = s.as_bytes();
+        bytes.iter().enumerate().any(|(i, chr)| {
+            self.options.inline_comment_prefixes.contains(chr)
+                && match i.checked_sub(1) {
+                    Some(prev) => is_indent(bytes[prev]),
+                    None => at_start,
+                }
+        })
+    }
+
+    fn assert_property(&self, key: &str, value: &str) {
+        if let Err(msg) = self.check_property(key, value) {
+            panic!("invalid property: {msg}");
+        }
+    }
+
+    /// Returns the delimiter used for new properties.
+    fn delimiter(&self) -> char {
+        self.options
+            .delimiters
+            .first()
+            .map_or('=', |&chr| char::from(chr))
+    }
+
+    /// Inserts a line, terminating it with the document newline.
+    ///
+    /// A missing newline at the end of the document is preserved.
+    fn insert_line(&mut self, index: usize, mut line: Line) {
+        let newline = self.newline();
+        line.newline = Some(newline);
+        if index == self.lines.len()
+            && let Some(last) = self.lines.last_mut()
+            && last.newline.is_none()
+        {
+            last.newline = Some(newline);
+            line.newline = None;
+        }
+        self.lines.insert(index, line);
+    }
+
+    /// Removes lines, preserving a missing newline at the end of the document.
+    fn remove_lines(&mut self, range: Range<usize>) {
+        let trailing = range.end == self.lines.len()
+            && self.lines.last().is_some_and(|line| line.newline.is_none());
+        self.lines.drain(range);
+        if trailing && let Some(last) = self.lines.last_mut() {
+            last.newline = None;
+        }
+    }
+
+    /// Finds the line index of the first matching property.
+    fn find(&self, section: Option<&str>, key: &str) -> Option<usize> {
+        self.bodies(section)
+            .flat_map(|body| body.clone().zip(&self.lines[body]))
+            .find_map(|(index, line)| (line.key() == Some(key)).then_some(index))
+    }
+
+    /// Returns the line ranges (excluding the header) of every section with
+    /// the given name.
+    fn bodies(&self, section: Option<&str>) -> impl Iterator<Item = Range<usize>> {
+        let lines = &self.lines;
+        let headers = lines
+            .iter()
+            .enumerate()
+            .filter(|(_, line)| line.is_header())
+            .map(|(index, _)| index);
+        // The global section has no header, start with that
+        core::iter::once(None)
+            .chain(headers.map(Some))
+            .filter(move |header| match *header {
+                None => section.is_none(),
+                Some(index) => section.is_some() && lines[index].section_name() == section,
+            })
+            .map(move |header| {
+                let start = header.map_or(0, |index| index + 1);
+                let end = lines[start..]
+                    .iter()
+                    .position(Line::is_header)
+                    .map_or(lines.len(), |len| start + len);
+                start..end
+            })
+    }
+}
+
+/// Builds a document from items, every line is terminated by [`Newline::Lf`].
+impl<'a> FromIterator<Item<'a>> for Document {
+    fn from_iter<T: IntoIterator<Item = Item<'a>>>(iter: T) -> Self {
+        iter.into_iter()
+            .map(|item| (item, Some(Newline::Lf)))
+            .collect()
+    }
+}
+
+/// Builds a document from items and their newlines, as returned by
+/// [`Parser::with_newlines`].
+impl<'a> FromIterator<(Item<'a>, Option<Newline>)> for Document {
+    fn from_iter<T: IntoIterator<Item = (Item<'a>, Option<Newline>)>>(iter: T) -> Self {
+        let mut doc = Self::new();
+        doc.extend(iter);
+        doc
+    }
+}
+
+impl<'a> Extend<Item<'a>> for Document {
+    fn extend<T: IntoIterator<Item = Item<'a>>>(&mut self, iter: T) {
+        self.extend(iter.into_iter().map(|item| (item, Some(Newline::Lf))));
+    }
+}
+
+impl<'a> Extend<(Item<'a>, Option<Newline>)> for Document {
+    fn extend<T: IntoIterator<Item = (Item<'a>, Option<Newline>)>>(&mut self, iter: T) {
+        self.lines.extend(
+            iter.into_iter()
+                .filter_map(|(item, newline)| Line::from_item(&item, newline)),
+        );
+    }
+}
+
+impl From<&str> for Document {
+    #[inline]
+    fn from(s: &str) -> Self {
+        Self::parse(s)
+    }
+}
+
+impl fmt::Display for Document {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut writer = Writer::new(f);
+        self.lines
+            .iter()
+            .try_for_each(|line| writer.write_line(&line.item(), line.newline))
+    }
+}
+
+/// Iterator over the items of a [`Document`].
+///
+/// See [`Document::items`].
+#[derive(Clone, Debug)]
+pub struct Items<'a> {
+    lines: core::slice::Iter<'a, Line>,
+    section_ended: bool,
+}
+
+impl<'a> Iterator for Items<'a> {
+    type Item = Item<'a>;
+
+    fn next(&mut self) -> Option<Item<'a>> {
+        // Mirror the SectionEnd logic of the parser
+        let Some(line) = self.lines.as_slice().first() else {
+            if self.section_ended {
+                return None;
+            }
+            self.section_ended = true;
+            return Some(Item::SectionEnd);
+        };
+        if line.is_header() {
+            if !self.section_ended {
+                self.section_ended = true;
+                return Some(Item::SectionEnd);
+            }
+            self.section_ended = false;
+        }
+        self.lines.next();
+        Some(line.item())
+    }
+}
+
+impl core::iter::FusedIterator for Items<'_> {}
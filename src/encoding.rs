@@ -0,0 +1,312 @@
+//! Decoding and encoding of documents.
+
+use alloc::borrow::Cow;
+use alloc::string::String;
+use alloc::vec::Vec;
+use core::fmt;
+use core::str;
+
+use crate::Parser;
+use crate::ParserOptions;
+
+/// Characters of Windows-1252 for the bytes `0x80` to `0x9F`.
+///
+/// The bytes undefined in Windows-1252 map to the C1 control characters (as
+/// in the WHATWG encoding standard), so every byte can be decoded.
+const WINDOWS_1252: [char; 32] = [
+    '\u{20AC}', '\u{0081}', '\u{201A}', '\u{0192}', '\u{201E}', '\u{2026}', '\u{2020}', '\u{2021}',
+    '\u{02C6}', '\u{2030}', '\u{0160}', '\u{2039}', '\u{0152}', '\u{008D}', '\u{017D}', '\u{008F}',
+    '\u{0090}', '\u{2018}', '\u{2019}', '\u{201C}', '\u{201D}', '\u{2022}', '\u{2013}', '\u{2014}',
+    '\u{02DC}', '\u{2122}', '\u{0161}', '\u{203A}', '\u{0153}', '\u{009D}', '\u{017E}', '\u{0178}',
+];
+
+/// Error returned when decoding or encoding fails.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum EncodingError {
+    /// The input is not valid in the encoding.
+    Invalid {
+        /// Byte offset of the invalid data in the input
+        offset: usize,
+    },
+    /// A character cannot be represented in the encoding.
+    Unmappable {
+        /// Byte offset of the character in the text
+        offset: usize,
+    },
+}
+
+impl fmt::Display for EncodingError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match *self {
+            Self::Invalid { offset } => write!(f, "invalid data at byte {offset}"),
+            Self::Unmappable { offset } => {
+                write!(f, "unmappable character at byte {offset}")
+            }
+        }
+    }
+}
+
+impl core::error::Error for EncodingError {}
+
+/// A text encoding.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum Encoding {
+    /// UTF-8 (the default).
+    #[default]
+    Utf8,
+    /// UTF-16, little endian.
+    Utf16Le,
+    /// UTF-16, big endian.
+    Utf16Be,
+    /// Windows-1252 (a superset of Latin-1).
+    Windows1252,
+}
+
+impl Encoding {
+    /// Detects the encoding from the byte order mark at the start of the
+    /// input, if any.
+    #[must_use]
+    pub fn detect(bytes: &[u8]) -> Option<Self> {
+        [Self::Utf8, Self::Utf16Le, Self::Utf16Be]
+            .into_iter()
+            .find(|encoding| bytes.starts_with(encoding.bom()))
+    }
+
+    /// Returns the byte order mark of the encoding.
+    ///
+    /// This is empty for [`Encoding::Windows1252`], which has none.
+    #[inline]
+    #[must_use]
+    pub const fn bom(self) -> &'static [u8] {
+        match self {
+            Self::Utf8 => b"\xEF\xBB\xBF",
+            Self::Utf16Le => b"\xFF\xFE",
+            Self::Utf16Be => b"\xFE\xFF",
+            Self::Windows1252 => b"",
+        }
+    }
+
+    /// Decodes the input (without byte order mark).
+    ///
+    /// UTF-8 input is borrowed, other encodings are decoded into a new
+    /// string.
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncodingError::Invalid`] if the input is not valid UTF-8,
+    /// or not valid UTF-16 (an odd length or an unpaired surrogate).
+    /// Windows-1252 decoding never fails.
+    pub fn decode(self, bytes: &[u8]) -> Result<Cow<'_, str>, EncodingError> {
+        match self {
+            Self::Utf8 => {
+                str::from_utf8(bytes)
+                    .map(Cow::Borrowed)
+                    .map_err(|err| EncodingError::Invalid {
+                        offset: err.valid_up_to(),
+                    })
+            }
+            Self::Utf16Le => decode_utf16(bytes, u16::from_le_bytes).map(Cow::Owned),
+            Self::Utf16Be => decode_utf16(bytes, u16::from_be_bytes).map(Cow::Owned),
+            Self::Windows1252 => {
+                let decode = |byte: u8| match byte {
+                    0x80..=0x9F => WINDOWS_1252[usize::from(byte - 0x80)],
+                    _ => char::from(byte),
+                };
+                Ok(match str::from_utf8(bytes) {
+                    // Plain ascii is the same in both encodings
+                    Ok(text) if text.is_ascii() => Cow::Borrowed(text),
+                    _ => Cow::Owned(bytes.iter().copied().map(decode).collect()),
+                })
+            }
+        }
+    }
+
+    /// Encodes the text (without byte order mark).
+    ///
+    /// # Errors
+    ///
+    /// Returns [`EncodingError::Unmappable`] for characters that cannot be
+    /// represented in Windows-1252. The other encodings never fail.
+    pub fn encode(self, text: &str) -> Result<Vec<u8>, EncodingError> {
+        match self {
+            Self::Utf8 => Ok(text.as_bytes().to_vec()),
+            Self::Utf16Le => Ok(text.encode_utf16().flat_map(u16::to_le_bytes).collect()),
+            Self::Utf16Be => Ok(text.encode_utf16().flat_map(u16::to_be_bytes).collect()),
+            Self::Windows1252 => text
+                .char_indices()
+                .map(|(offset, chr)| match u8::try_from(chr) {
+                    Ok(byte) if !(0x80..=0x9F).contains(&byte) => Ok(byte),
+                    _ => WINDOWS_1252
+                        .iter()
+                        .position(|&other| other == chr)
+                        .and_then(|index| u8::try_from(index + 0x80).ok())
+                        .ok_or(EncodingError::Unmappable { offset }),
+                })
+                .collect(),
+        }
+    }
+}
+
+/// Decodes UTF-16 code units read with `from_bytes`.
+fn decode_utf16(bytes: &[u8], from_bytes: fn([u8; 2]) -> u16) -> Result<String, EncodingError> {
+    let (units, rest) = bytes.as_chunks::<2>();
+    let mut text = String::with_capacity(bytes.len() / 2);
+    let mut offset = 0;
+    for chr in char::decode_utf16(units.iter().copied().map(from_bytes)) {
+        let chr = chr.map_err(|_| EncodingError::Invalid { offset })?;
+        offset += chr.len_utf16() * 2;
+        text.push(chr);
+    }
+    if !rest.is_empty() {
+        return Err(EncodingError::Invalid { offset });
+    }
+    Ok(text)
+}
+
+/// A decoded document, remembering its encoding.
+///
+/// The encoding is detected from the byte order mark, if there is one, which
+/// is not part of the decoded text. Otherwise the input is expected to be
+/// UTF-8, or a fallback encoding is used (see
+/// [`decode_with_fallback`](Decoded::decode_with_fallback)).
+///
+/// The document can be written back in the same encoding (and with the same
+/// byte order mark) using [`encode`](Decoded::encode):
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let input = b"\xFF\xFE[\0S\0]\0\n\0k\0=\0v\0";
+/// let decoded = ini::Decoded::decode(input).unwrap();
+/// assert_eq!(decoded.encoding(), ini::Encoding::Utf16Le);
+/// assert_eq!(decoded.text(), "[S]\nk=v");
+///
+/// let mut doc = ini::Document::parse(decoded.text());
+/// doc.set(Some("S"), "k", "w");
+/// assert_eq!(decoded.encode(&doc.to_string()).unwrap(), b"\xFF\xFE[\0S\0]\0\n\0k\0=\0w\0");
+/// ```
+///
+/// Requires the `alloc` feature.
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Decoded<'a> {
+    text: Cow<'a, str>,
+    encoding: Encoding,
+    bom: bool,
+}
+
+impl<'a> Decoded<'a> {
+    /// Decodes a document, detecting the encoding from the byte order mark.
+    ///
+    /// Without a byte order mark, the input must be UTF-8 and is borrowed.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid in the detected encoding.
+    #[inline]
+    pub fn decode(bytes: &'a [u8]) -> Result<Self, EncodingError> {
+        Self::decode_with_fallback(bytes, Encoding::Utf8)
+    }
+
+    /// Decodes a document, detecting the encoding from the byte order mark.
+    ///
+    /// Without a byte order mark, the input is decoded as UTF-8 if it is
+    /// valid UTF-8 and using `fallback` otherwise.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let decoded = ini::Decoded::decode_with_fallback(b"Name = Caf\xE9", ini::Encoding::Windows1252).unwrap();
+    /// assert_eq!(decoded.encoding(), ini::Encoding::Windows1252);
+    /// assert_eq!(decoded.text(), "Name = Café");
+    /// ```
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the input is not valid in the detected or
+    /// fallback encoding.
+    pub fn decode_with_fallback(
+        bytes: &'a [u8],
+        fallback: Encoding,
+    ) -> Result<Self, EncodingError> {
+        let (encoding, bom, text) = match Encoding::detect(bytes) {
+            Some(encoding) => {
+                let skip = encoding.bom().len();
+                let text = encoding.decode(&bytes[skip..]).map_err(|err| match err {
+                    EncodingError::Invalid { offset } => EncodingError::Invalid {
+                        offset: offset + skip,
+                    },
+                    err @ EncodingError::Unmappable { .. } => err,
+                })?;
+                (encoding, true, text)
+            }
+            None => match Encoding::Utf8.decode(bytes) {
+                Ok(text) => (Encoding::Utf8, false, text),
+                Err(_) if fallback != Encoding::Utf8 => (fallback, false, fallback.decode(bytes)?),
+                Err(err) => return Err(err),
+            },
+        };
+        Ok(Self {
+            text,
+            encoding,
+            bom,
+        })
+    }
+
+    /// Returns the decoded text (without byte order mark).
+    #[inline]
+    #[must_use]
+    pub fn text(&self) -> &str {
+        &self.text
+    }
+
+    /// Returns the encoding of the document.
+    #[inline]
+    #[must_use]
+    pub const fn encoding(&self) -> Encoding {
+        self.encoding
+    }
+
+    /// Returns true if the document started with a byte order mark.
+    #[inline]
+    #[must_use]
+    pub const fn has_bom(&self) -> bool {
+        self.bom
+    }
+
+    /// Returns a parser for the decoded text.
+    #[inline]
+    #[must_use]
+    pub fn parser(&self) -> Parser<'_> {
+        Parser::new(&self.text)
+    }
+
+    /// Returns a parser for the decoded text using the given options.
+    #[inline]
+    #[must_use]
+    pub fn parser_with_options(&self, options: ParserOptions) -> Parser<'_> {
+        Parser::with_options(&self.text, options)
+    }
+
+    /// Encodes text in the encoding of the document, adding the byte order
+    /// mark if the document had one.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the text cannot be represented in the encoding.
+    pub fn encode(&self, text: &str) -> Result<Vec<u8>, EncodingError> {
+        let mut bytes = self.encoding.encode(text)?;
+        if self.bom {
+            bytes.splice(..0, self.encoding.bom().iter().copied());
+        }
+        Ok(bytes)
+    }
+
+    /// Consumes the document, returning the decoded text.
+    #[inline]
+    #[must_use]
+    pub fn into_text(self) -> Cow<'a, str> {
+        self.text
+    }
+}
@@ -0,0 +1,104 @@
+//! Fast lookup of values without parsing the whole document.
+
+use crate::Item;
+use crate::Parser;
+use crate::ParserOptions;
+use crate::parse;
+
+/// Returns the value of a property in a document.
+///
+/// Sections are identified by name, with `None` referring to the properties
+/// before the first section header. If a section or key occurs multiple
+/// times, the first occurrence is returned. Returns `None` if the property
+/// does not exist or has no value (there is no `=` on the line).
+///
+/// Only sections with a matching name are parsed, all other sections are
+/// skipped by scanning for lines starting with `[`. The document is parsed
+/// with the default [`ParserOptions`].
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let document = "global = 1\n[a]\nkey = x\n[b]\nkey = y\n";
+/// assert_eq!(ini::find(document, Some("b"), "key"), Some("y"));
+/// assert_eq!(ini::find(document, None, "global"), Some("1"));
+/// assert_eq!(ini::find(document, None, "key"), None);
+/// ```
+#[must_use]
+pub fn find<'a>(s: &'a str, section: Option<&str>, key: &str) -> Option<&'a str> {
+    let [value] = find_many(s, &[(section, key)]);
+    value
+}
+
+/// Returns the values of several properties in a document.
+///
+/// This is the equivalent of calling [`find`] for every `(section, key)`
+/// pair, but the document is only scanned once (and only as far as needed to
+/// find every property).
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let document = "[a]\nkey = x\n[b]\nkey = y\n";
+/// let values = ini::find_many(document, &[(Some("b"), "key"), (Some("c"), "key")]);
+/// assert_eq!(values, [Some("y"), None]);
+/// ```
+#[must_use]
+pub fn find_many<'a, const N: usize>(
+    s: &'a str,
+    keys: &[(Option<&str>, &str); N],
+) -> [Option<&'a str>; N] {
+    let mut values = [None; N];
+    let mut pending = [true; N];
+    // The section of the body at the start of `rest`, `None` after a malformed
+    // section header (which no key matches)
+    let mut section = Some(None);
+    let mut rest = s;
+    loop {
+        let wanted = |(index, &(name, _)): (usize, &(Option<&str>, &str))| {
+            pending[index] && section == Some(name)
+        };
+        if keys.iter().enumerate().any(wanted) {
+            let mut parser = Parser::new(rest);
+            for item in parser.by_ref() {
+                let (key, val) = match item {
+                    Item::SectionEnd => break,
+                    Item::Property { key, val, .. } => (key, val),
+                    _ => continue,
+                };
+                for (index, &(name, other)) in keys.iter().enumerate() {
+                    if pending[index] && section == Some(name) && other == key {
+                        values[index] = val;
+                        pending[index] = false;
+                    }
+                }
+            }
+            rest = parser.remainder();
+        } else {
+            rest = skip_body(rest);
+        }
+        if rest.is_empty() || !pending.contains(&true) {
+            return values;
+        }
+        // The remainder starts with a section header
+        let mut parser = Parser::resume(rest, ParserOptions::new(), 0, true);
+        section = match parser.next() {
+            Some(Item::Section { name, .. }) => Some(Some(name)),
+            _ => None,
+        };
+        rest = parser.remainder();
+    }
+}
+
+/// Skips to the start of the first line starting with `[`.
+fn skip_body(s: &str) -> &str {
+    let bytes = s.as_bytes();
+    let mut i = 0;
+    while i < bytes.len() && bytes[i] != b'[' {
+        i += parse::find_nl(&bytes[i..]);
+        while matches!(bytes.get(i), Some(b'\r' | b'\n')) {
+            i += 1;
+        }
+    }
+    &s[i..]
+}
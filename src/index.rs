@@ -0,0 +1,237 @@
+//! Offset index for repeated lookups in a read-only document.
+
+use alloc::vec::Vec;
+use core::ops::Range;
+
+use crate::Item;
+use crate::Parser;
+use crate::ParserOptions;
+use crate::subrange;
+
+/// Compact byte range into the input.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Span32 {
+    start: u32,
+    end: u32,
+}
+
+impl Span32 {
+    fn new(range: Range<usize>) -> Self {
+        Self {
+            start: to_u32(range.start),
+            end: to_u32(range.end),
+        }
+    }
+
+    fn get(self, s: &str) -> &str {
+        &s[self.start as usize..self.end as usize]
+    }
+}
+
+/// Converts an offset, the input is checked to fit when building the index.
+#[inline]
+fn to_u32(offset: usize) -> u32 {
+    debug_assert!(u32::try_from(offset).is_ok());
+    offset as u32
+}
+
+/// A well-formed section header.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Header {
+    name: Span32,
+    offset: u32,
+}
+
+/// A property, `section` is the index of its header plus one (zero for the
+/// properties before the first section header).
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+struct Entry {
+    section: u32,
+    key: Span32,
+    val: Option<Span32>,
+    offset: u32,
+}
+
+/// Index of the sections and properties of a read-only document.
+///
+/// The index is built in a single pass over a [`Parser`] and stores compact
+/// byte offsets into the input, sorted by name. Lookups are `O(log n)` and
+/// return slices of the original input.
+///
+/// Like [`Document`](crate::Document) sections are identified by name, with
+/// `None` referring to the properties before the first section header. If a
+/// section or key occurs multiple times, lookups operate on the first
+/// occurrence. Properties after a malformed section header are not indexed.
+///
+/// ```
+/// use ini_roundtrip::Index;
+///
+/// let document = "global = 1\n[a]\nkey = x\n[b]\nkey = y\n";
+/// let index = Index::parse(document);
+/// assert_eq!(index.get(Some("b"), "key"), Some("y"));
+/// assert_eq!(index.get(None, "global"), Some("1"));
+/// assert_eq!(index.section_offset(Some("b")), Some(23));
+/// assert_eq!(index.property_offset(Some("b"), "key"), Some(27));
+/// assert_eq!(index.sections().collect::<Vec<_>>(), ["a", "b"]);
+/// ```
+#[derive(Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Index<'a> {
+    source: &'a str,
+    /// Section headers in document order.
+    headers: Vec<Header>,
+    /// Indices into `headers`, sorted by name and then document order.
+    sorted_headers: Vec<u32>,
+    /// Properties sorted by section name, key and then document order.
+    entries: Vec<Entry>,
+}
+
+impl<'a> Index<'a> {
+    /// Builds the index of a document.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input is larger than `u32::MAX` bytes.
+    #[must_use]
+    pub fn parse(s: &'a str) -> Self {
+        Self::parse_with_options(s, ParserOptions::new())
+    }
+
+    /// Builds the index of a document using the given dialect.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the input is larger than `u32::MAX` bytes.
+    #[must_use]
+    pub fn parse_with_options(s: &'a str, options: ParserOptions) -> Self {
+        assert!(
+            u32::try_from(s.len()).is_ok(),
+            "input too large for an index"
+        );
+        let mut headers = Vec::new();
+        let mut entries = Vec::new();
+        // Index of the current header plus one, `None` after a malformed header
+        let mut section = Some(0);
+        let mut parser = Parser::with_options(s, options);
+        loop {
+            let offset = to_u32(parser.offset());
+            match parser.next() {
+                None => break,
+                Some(Item::Section { name, .. }) => {
+                    headers.push(Header {
+                        name: Span32::new(subrange(s, name)),
+                        offset,
+                    });
+                    section = Some(to_u32(headers.len()));
+                }
+                Some(Item::Error(_)) => section = None,
+                Some(Item::Property { key, val, .. }) => {
+                    if let Some(section) = section {
+                        entries.push(Entry {
+                            section,
+                            key: Span32::new(subrange(s, key)),
+                            val: val.map(|val| Span32::new(subrange(s, val))),
+                            offset,
+                        });
+                    }
+                }
+                Some(_) => {}
+            }
+        }
+
+        // Stable sorts keep the first occurrence first
+        let name = |header: u32| headers[header as usize].name.get(s);
+        let mut sorted_headers: Vec<_> = (0..to_u32(headers.len())).collect();
+        sorted_headers.sort_by_key(|&header| name(header));
+        entries.sort_by_key(|entry| (entry.section.checked_sub(1).map(name), entry.key.get(s)));
+        Self {
+            source: s,
+            headers,
+            sorted_headers,
+            entries,
+        }
+    }
+
+    /// Returns the indexed input.
+    #[inline]
+    #[must_use]
+    pub const fn source(&self) -> &'a str {
+        self.source
+    }
+
+    /// Returns an iterator over the names of all well-formed sections, in
+    /// document order.
+    pub fn sections(&self) -> impl Iterator<Item = &'a str> + use<'a, '_> {
+        self.headers
+            .iter()
+            .map(|header| header.name.get(self.source))
+    }
+
+    /// Returns true if the given section exists.
+    ///
+    /// The `None` section (properties before the first section header) always
+    /// exists.
+    #[must_use]
+    pub fn contains_section(&self, section: Option<&str>) -> bool {
+        self.section_offset(section).is_some()
+    }
+
+    /// Returns the byte offset of the header of the given section.
+    ///
+    /// This is `0` for the `None` section.
+    #[must_use]
+    pub fn section_offset(&self, section: Option<&str>) -> Option<usize> {
+        let Some(section) = section else {
+            return Some(0);
+        };
+        let start = self
+            .sorted_headers
+            .partition_point(|&header| self.header_name(header) < section);
+        let header = *self.sorted_headers.get(start)?;
+        (self.header_name(header) == section).then(|| self.headers[header as usize].offset as usize)
+    }
+
+    /// Returns true if the given property exists in the given section.
+    #[must_use]
+    pub fn contains_key(&self, section: Option<&str>, key: &str) -> bool {
+        self.find(section, key).is_some()
+    }
+
+    /// Returns the trimmed value of a property.
+    ///
+    /// Returns `None` if the property does not exist or has no value (there is
+    /// no `=` on the line).
+    #[must_use]
+    pub fn get(&self, section: Option<&str>, key: &str) -> Option<&'a str> {
+        Some(self.find(section, key)?.val?.get(self.source))
+    }
+
+    /// Returns the byte offset of the line of a property.
+    #[must_use]
+    pub fn property_offset(&self, section: Option<&str>, key: &str) -> Option<usize> {
+        Some(self.find(section, key)?.offset as usize)
+    }
+
+    /// Finds the first matching property.
+    fn find(&self, section: Option<&str>, key: &str) -> Option<&Entry> {
+        let target = (section, key);
+        let start = self
+            .entries
+            .partition_point(|entry| self.entry_key(entry) < target);
+        let entry = self.entries.get(start)?;
+        (self.entry_key(entry) == target).then_some(entry)
+    }
+
+    /// Returns the name of a header by index.
+    fn header_name(&self, header: u32) -> &'a str {
+        self.headers[header as usize].name.get(self.source)
+    }
+
+    /// Returns the section name and key of a property.
+    fn entry_key(&self, entry: &Entry) -> (Option<&'a str>, &'a str) {
+        let section = entry
+            .section
+            .checked_sub(1)
+            .map(|header| self.header_name(header));
+        (section, entry.key.get(self.source))
+    }
+}
@@ -8,14 +8,15 @@ Features:
 * Fast!
 * Streaming
 * `no_std` support
+* Parsing of byte slices that are not valid UTF-8 with [`ByteParser`]
+* Optional owned, editable `Document` (requires the `alloc` feature)
 
 Caveats:
 * The Display trait on [Item] does *not* preserve formatting, if this is
-  something you want, make sure to use the `raw` attributes to extract
-  the raw line instead.
-* Newlines are not saved. It is up to the caller to keep track of the
-  type of newline in use. Mixed newline (e.g. a mix of CR, CRLF and LF) is
-  supported on loading, but not on saving.
+  something you want, use a [`Writer`] (or the `raw` attributes) instead.
+* Newlines are not part of the [Item]s. Use [`Parser::with_newlines`] to
+  get the [`Newline`] terminating each line, this allows reproducing mixed
+  newlines (e.g. a mix of CR, CRLF and LF) and a missing trailing newline.
 
 ## Examples
 
@@ -29,9 +30,9 @@ Key = Value  ";
 
 let elements = [
     ini::Item::SectionEnd,
-    ini::Item::Section{name: "SECTION", raw: "[SECTION]"},
+    ini::Item::Section{name: "SECTION", comment: None, raw: "[SECTION]"},
     ini::Item::Comment{raw: ";this is a comment"},
-    ini::Item::Property{key: "Key", val: Some("Value"), raw: "Key = Value  "},
+    ini::Item::Property{key: "Key", val: Some("Value"), comment: None, raw: "Key = Value  "},
     ini::Item::SectionEnd,
 ];
 
@@ -55,7 +56,7 @@ nonsense";
 let elements = [
     ini::Item::SectionEnd,
     ini::Item::Error("[SECTION"),
-    ini::Item::Property{key: "nonsense", val: None, raw: "nonsense"},
+    ini::Item::Property{key: "nonsense", val: None, comment: None, raw: "nonsense"},
     ini::Item::SectionEnd,
 ];
 
@@ -80,18 +81,84 @@ INI is not a well specified format, this parser tries to make as little assumpti
 
 Padding whitespace is always trimmed, but the raw line is always stored as well.
 
+Some aspects of the syntax (such as the comment prefixes and delimiters) can be
+configured using [`ParserOptions`].
+
 No further processing of the input is done, e.g. if escape sequences are necessary they must be processed by the caller.
+
+Crate features
+--------------
+
+* `alloc`: Enables `Document`, an owned and editable document model built
+  on top of the [`Parser`], `Index` for repeated lookups in a read-only
+  document, and `Decoded` for decoding documents with a byte
+  order mark, in UTF-16 or in Windows-1252 (and encoding them again).
+* `std`: Enables `IoWriter` for writing to `std::io::Write` and
+  `ReaderParser` for parsing from `std::io::BufRead`. On x86 without
+  AVX-512BW enabled at compile time, AVX2 and AVX-512BW are detected and used
+  at runtime. Implies `alloc`.
+* `rayon`: Enables the `par` module, parsing large documents in parallel
+  with [rayon](https://docs.rs/rayon). Implies `std`.
+* `serde`: Enables the `de` module, deserializing documents into Rust types
+  with [serde](https://serde.rs), and the `ser` module, updating a
+  `Document` from Rust types. Implies `alloc`.
 */
 
 #![no_std]
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+#[cfg(feature = "std")]
+extern crate std;
+
 use core::fmt;
 use core::str;
 
-/// SAFETY: All the routines here work only with and slice only at ascii
-/// characters, and the user provided input is a &str. This means this crate
-/// cannot create invalid UTF-8 strings out of thin air and conversion between
-/// `&str` and `&[u8]` is a noop even when slicing
+pub use continuation::Continuation;
+pub use continuation::ValueLines;
+#[cfg(feature = "alloc")]
+pub use document::Document;
+#[cfg(feature = "alloc")]
+pub use document::Items;
+#[cfg(feature = "alloc")]
+pub use encoding::Decoded;
+#[cfg(feature = "alloc")]
+pub use encoding::Encoding;
+#[cfg(feature = "alloc")]
+pub use encoding::EncodingError;
+pub use find::find;
+pub use find::find_many;
+#[cfg(feature = "alloc")]
+pub use index::Index;
+pub use options::ParserOptions;
+pub use push::PushError;
+pub use push::PushParser;
+#[cfg(feature = "std")]
+pub use reader::ReaderParser;
+pub use sections::Section;
+pub use sections::SectionItems;
+pub use sections::Sections;
+pub use sections::WithSection;
+pub use span::Span;
+pub use span::Spanned;
+pub use span::SpannedItems;
+pub use strict::ErrorKind;
+pub use strict::Lossy;
+pub use strict::ParseError;
+pub use strict::Strict;
+#[cfg(feature = "std")]
+pub use writer::IoWriter;
+pub use writer::Mode;
+pub use writer::Writer;
+
+/// SAFETY: [`ByteParser`] accepts arbitrary bytes, but it only ever splits
+/// its input at ascii bytes (newlines, delimiters, brackets and comment
+/// prefixes, which [`ParserOptions`] requires to be ascii) and only trims
+/// ascii whitespace. An ascii byte is never part of a multi-byte character,
+/// so every slice of valid UTF-8 it returns is valid UTF-8 as well. This is
+/// only called on such slices: [`Parser`] feeds `ByteParser` a `&str`, and
+/// the other callers validate the bytes (or the `raw` line of an item) first.
+/// Debug builds check the result.
 #[inline]
 fn from_utf8(v: &[u8]) -> &str {
     #[cfg(not(debug_assertions))]
@@ -100,39 +167,98 @@ fn from_utf8(v: &[u8]) -> &str {
     return str::from_utf8(v).expect("Impossible: Non-UTF8");
 }
 
-/// Trims ascii whitespace from the start and end of the string slice.
-fn trim(s: &str) -> &str {
-    s.trim_matches(|chr: char| chr.is_ascii_whitespace())
+/// Returns the byte range of `inner` within `outer`.
+///
+/// `inner` must be a sub-slice of `outer`.
+#[inline]
+fn subrange(outer: &str, inner: &str) -> core::ops::Range<usize> {
+    let start = inner.as_ptr() as usize - outer.as_ptr() as usize;
+    debug_assert!(start + inner.len() <= outer.len());
+    start..start + inner.len()
+}
+
+/// Returns true for whitespace that may indent a line.
+#[inline]
+const fn is_indent(chr: u8) -> bool {
+    matches!(chr, b' ' | b'\t' | b'\x0C')
+}
+
+/// Trims ascii whitespace from the start and end of the slice.
+///
+/// Unlike `<[u8]>::trim_ascii` a slice of only whitespace is trimmed to the
+/// empty slice at its start (like `str::trim_matches`).
+fn trim(s: &[u8]) -> &[u8] {
+    match s.iter().position(|chr| !chr.is_ascii_whitespace()) {
+        Some(start) => s[start..].trim_ascii_end(),
+        None => &s[..0],
+    }
+}
+
+/// The terminator of a line.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Newline {
+    /// `"\n"`
+    #[default]
+    Lf,
+    /// `"\r\n"`
+    CrLf,
+    /// `"\r"`
+    Cr,
+}
+
+impl Newline {
+    /// Returns the newline as a string.
+    #[inline]
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::Lf => "\n",
+            Self::CrLf => "\r\n",
+            Self::Cr => "\r",
+        }
+    }
+}
+
+impl fmt::Display for Newline {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
 }
 
 /// A parsed element of syntatic meaning
-#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
-pub enum Item<'a> {
+///
+/// The fields are string slices of the input, or byte slices for items
+/// returned by [`ByteParser`].
+#[derive(Debug, Eq, PartialEq, Hash)]
+pub enum Item<'a, T: ?Sized = str> {
     /// Syntax error.
     ///
     /// Section header element was malformed.
     /// Malformed section headers are defined by a line starting with `[` but
-    /// not ending with `]`.
+    /// not ending with `]`. Use [`Parser::strict`] to find out what is wrong.
     ///
     /// ```
     /// assert_eq!(
     ///     ini_roundtrip::Parser::new("[Error").nth(1),
     ///     Some(ini_roundtrip::Item::Error("[Error")));
     /// ```
-    Error(&'a str),
+    Error(&'a T),
 
     /// Section header element.
     ///
     /// ```
     /// assert_eq!(
     ///     ini_roundtrip::Parser::new("[Section]").nth(1),
-    ///     Some(ini_roundtrip::Item::Section{name: "Section", raw: "[Section]"}));
+    ///     Some(ini_roundtrip::Item::Section{name: "Section", comment: None, raw: "[Section]"}));
     /// ```
     Section {
         /// Trimmed name of the section
-        name: &'a str,
+        name: &'a T,
+        /// Trimmed trailing comment (including the prefix), if inline comments
+        /// are enabled (see [`ParserOptions::inline_comment_prefixes`])
+        comment: Option<&'a T>,
         /// Raw line
-        raw: &'a str,
+        raw: &'a T,
     },
 
     /// End of section.
@@ -157,18 +283,21 @@ pub enum Item<'a> {
     /// ```
     /// assert_eq!(
     ///     ini_roundtrip::Parser::new("Key=Value").next(),
-    ///     Some(ini_roundtrip::Item::Property{key: "Key", val: Some("Value"), raw: "Key=Value"}));
+    ///     Some(ini_roundtrip::Item::Property{key: "Key", val: Some("Value"), comment: None, raw: "Key=Value"}));
     /// assert_eq!(
     ///     ini_roundtrip::Parser::new("Key").next(),
-    ///     Some(ini_roundtrip::Item::Property{key: "Key", val: None, raw: "Key"}));
+    ///     Some(ini_roundtrip::Item::Property{key: "Key", val: None, comment: None, raw: "Key"}));
     /// ```
     Property {
         /// Trimmed key
-        key: &'a str,
+        key: &'a T,
         /// Trimmed value (if any)
-        val: Option<&'a str>,
+        val: Option<&'a T>,
+        /// Trimmed trailing comment (including the prefix), if inline comments
+        /// are enabled (see [`ParserOptions::inline_comment_prefixes`])
+        comment: Option<&'a T>,
         /// Raw line
-        raw: &'a str,
+        raw: &'a T,
     },
 
     /// Comment.
@@ -180,7 +309,7 @@ pub enum Item<'a> {
     /// ```
     Comment {
         /// Raw line
-        raw: &'a str,
+        raw: &'a T,
     },
 
     /// Blank line.
@@ -195,43 +324,85 @@ pub enum Item<'a> {
     /// ```
     Blank {
         /// Raw line
-        raw: &'a str,
+        raw: &'a T,
     },
 }
 
-impl fmt::Display for Item<'_> {
-    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+impl<T: ?Sized> Clone for Item<'_, T> {
+    #[inline]
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T: ?Sized> Copy for Item<'_, T> {}
+
+impl<'a, T: ?Sized> Item<'a, T> {
+    /// Returns the raw line(s) of the item, `None` for [`Item::SectionEnd`].
+    #[inline]
+    const fn raw(&self) -> Option<&'a T> {
         match *self {
-            Item::Error(error) => writeln!(f, "{error}"),
-            Item::Section { name, raw: _ } => writeln!(f, "[{name}]"),
-            Item::SectionEnd => Ok(()),
-            Item::Property {
-                key,
-                val: Some(value),
-                raw: _,
-            } => writeln!(f, "{key}={value}"),
+            Item::Error(raw)
+            | Item::Section { raw, .. }
+            | Item::Property { raw, .. }
+            | Item::Comment { raw }
+            | Item::Blank { raw } => Some(raw),
+            Item::SectionEnd => None,
+        }
+    }
+
+    /// Converts the fields of the item.
+    #[inline]
+    fn map<U: ?Sized>(self, f: impl Fn(&'a T) -> &'a U) -> Item<'a, U> {
+        match self {
+            Item::Error(raw) => Item::Error(f(raw)),
+            Item::Section { name, comment, raw } => Item::Section {
+                name: f(name),
+                comment: comment.map(&f),
+                raw: f(raw),
+            },
+            Item::SectionEnd => Item::SectionEnd,
             Item::Property {
                 key,
-                val: None,
-                raw: _,
-            } => writeln!(f, "{key}"),
-            Item::Comment { raw: comment } => writeln!(f, ";{comment}"),
-            Item::Blank { raw: _ } => f.write_str("\n"),
+                val,
+                comment,
+                raw,
+            } => Item::Property {
+                key: f(key),
+                val: val.map(&f),
+                comment: comment.map(&f),
+                raw: f(raw),
+            },
+            Item::Comment { raw } => Item::Comment { raw: f(raw) },
+            Item::Blank { raw } => Item::Blank { raw: f(raw) },
         }
     }
 }
 
+impl fmt::Display for Item<'_> {
+    /// Writes the item in canonical form (see [`Mode::Canonical`]), followed
+    /// by a `"\n"`. Nothing is written for [`Item::SectionEnd`].
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if *self == Item::SectionEnd {
+            return Ok(());
+        }
+        writer::write_canonical(f, self, '=')?;
+        f.write_str("\n")
+    }
+}
+
 /// Ini streaming parser.
 ///
 /// The whole document must be available before parsing starts.
-/// The parser then returns each element as it is being parsed.
+/// The parser then returns each element as it is being parsed. To parse from
+/// a reader instead, use `ReaderParser` (requires the `std` feature), or push
+/// chunks of input into a [`PushParser`]. To parse input that may not be
+/// valid UTF-8, use a [`ByteParser`].
 ///
 /// See [`crate`] documentation for more information.
 #[derive(Clone, Debug)]
 pub struct Parser<'a> {
-    line: u32,
-    section_ended: bool,
-    state: &'a [u8],
+    bytes: ByteParser<'a>,
 }
 
 impl<'a> Parser<'a> {
@@ -239,14 +410,275 @@ impl<'a> Parser<'a> {
     #[inline]
     #[must_use]
     pub const fn new(s: &'a str) -> Self {
-        let state = s.as_bytes();
+        Self::with_options(s, ParserOptions::new())
+    }
+
+    /// Constructs a new `Parser` instance for the given dialect.
+    #[inline]
+    #[must_use]
+    pub const fn with_options(s: &'a str, options: ParserOptions) -> Self {
+        Parser {
+            bytes: ByteParser::with_options(s.as_bytes(), options),
+        }
+    }
+
+    /// Constructs a `Parser` continuing at the start of `s` with the state of
+    /// a previous parser.
+    #[inline]
+    pub(crate) const fn resume(
+        s: &'a str,
+        options: ParserOptions,
+        line: u32,
+        section_ended: bool,
+    ) -> Self {
         Parser {
+            bytes: ByteParser::resume(s.as_bytes(), options, line, section_ended),
+        }
+    }
+
+    /// Returns the options of the parser.
+    #[inline]
+    #[must_use]
+    pub const fn options(&self) -> &ParserOptions {
+        self.bytes.options()
+    }
+
+    /// Returns the line number the parser is currently at.
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.bytes.line()
+    }
+
+    /// Returns the byte offset in the input the parser is currently at.
+    #[inline]
+    #[must_use]
+    pub const fn offset(&self) -> usize {
+        self.bytes.offset()
+    }
+
+    /// Returns the newline that terminated the line of the most recently
+    /// returned item.
+    ///
+    /// This is `None` before the first item, for [`Item::SectionEnd`] and for
+    /// the last line if the document does not end with a newline.
+    #[inline]
+    #[must_use]
+    pub const fn newline(&self) -> Option<Newline> {
+        self.bytes.newline()
+    }
+
+    /// Returns an iterator adapter yielding each item together with the
+    /// newline that terminated its line.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let mut iter = ini::Parser::new("[SECTION]\r\nKey=Value").with_newlines();
+    /// assert_eq!(iter.next(), Some((ini::Item::SectionEnd, None)));
+    /// assert_eq!(
+    ///     iter.next(),
+    ///     Some((ini::Item::Section{name: "SECTION", comment: None, raw: "[SECTION]"}, Some(ini::Newline::CrLf))));
+    /// assert_eq!(
+    ///     iter.next(),
+    ///     Some((ini::Item::Property{key: "Key", val: Some("Value"), comment: None, raw: "Key=Value"}, None)));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_newlines(self) -> WithNewlines<'a> {
+        WithNewlines { parser: self }
+    }
+
+    /// Returns an iterator adapter yielding each item together with its
+    /// location in the input.
+    ///
+    /// See [`Spanned`] for details.
+    #[inline]
+    #[must_use]
+    pub const fn spanned(self) -> SpannedItems<'a> {
+        SpannedItems::new(self)
+    }
+
+    /// Returns an iterator adapter grouping the items by section.
+    ///
+    /// The first section is the implicit unnamed section holding the items
+    /// before the first section header, it is returned even if it is empty.
+    /// No allocation is performed, the items of each section are parsed when
+    /// iterating over them.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let document = "global=1\n[a]\nkey=x\n; comment\n[b]\nkey=y\n";
+    /// let mut sections = ini::Parser::new(document).sections();
+    /// let global = sections.next().unwrap();
+    /// assert_eq!((global.name(), global.get("global")), (None, Some("1")));
+    /// let a = sections.next().unwrap();
+    /// assert_eq!((a.name(), a.raw()), (Some("a"), Some("[a]")));
+    /// assert_eq!(a.items().count(), 2);
+    /// assert_eq!(a.properties().collect::<Vec<_>>(), [("key", Some("x"))]);
+    /// assert_eq!(sections.next().unwrap().get("key"), Some("y"));
+    /// assert!(sections.next().is_none());
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn sections(self) -> Sections<'a> {
+        Sections::new(self)
+    }
+
+    /// Returns an iterator adapter yielding each item together with the name
+    /// of the section it belongs to.
+    ///
+    /// The name is `None` before the first section header and after a
+    /// malformed section header. A section header belongs to the section it
+    /// starts, and [`Item::SectionEnd`] to the section it ends.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let document = "key=global\n[a]\nkey=x\n[b]\nkey=y\n";
+    /// let value = ini::Parser::new(document)
+    ///     .with_section()
+    ///     .find_map(|(section, item)| match item {
+    ///         ini::Item::Property { key: "key", val, .. } if section == Some("b") => val,
+    ///         _ => None,
+    ///     });
+    /// assert_eq!(value, Some("y"));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn with_section(self) -> WithSection<'a> {
+        WithSection::new(self)
+    }
+
+    /// Returns an iterator adapter that reports syntax errors as
+    /// [`ParseError`]s.
+    ///
+    /// Besides malformed section headers (which are returned as
+    /// [`Item::Error`] otherwise), empty section names and properties with a
+    /// value but an empty key are reported as errors. All other items are
+    /// returned unchanged.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let document = "[Section]\nKey = Value\n[Section] x\n";
+    /// let result: Result<Vec<_>, _> = ini::Parser::new(document).strict().collect();
+    /// let error = result.unwrap_err();
+    /// assert_eq!(error.kind(), ini::ErrorKind::TrailingCharacters);
+    /// assert_eq!((error.line(), error.column()), (2, 10));
+    /// assert_eq!(error.to_string(), "line 3, column 11: trailing characters after section header");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn strict(self) -> Strict<'a> {
+        Strict::new(self)
+    }
+
+    /// Returns the remainder of the input string.
+    #[inline]
+    #[must_use]
+    pub fn remainder(&self) -> &'a str {
+        from_utf8(self.bytes.remainder())
+    }
+
+    /// Returns the state needed to resume parsing (see [`Parser::resume`]).
+    #[inline]
+    pub(crate) const fn section_ended(&self) -> bool {
+        self.bytes.section_ended()
+    }
+
+    /// Splits a line (without newline) at the start of an inline comment.
+    #[inline]
+    pub(crate) fn split_inline_comment(&self, line: &'a [u8]) -> (&'a [u8], Option<&'a [u8]>) {
+        self.bytes.split_inline_comment(line)
+    }
+}
+
+impl<'a> Iterator for Parser<'a> {
+    type Item = Item<'a>;
+
+    #[inline]
+    fn next(&mut self) -> Option<Item<'a>> {
+        // The input is a &str and the parser only splits at ascii bytes
+        Some(self.bytes.next()?.map(from_utf8))
+    }
+}
+
+impl core::iter::FusedIterator for Parser<'_> {}
+
+/// Ini streaming parser for byte slices.
+///
+/// This is the equivalent of [`Parser`] for input that may not be valid
+/// UTF-8 (such as Latin-1 encoded files). The syntax is the same, but the
+/// fields of the returned items are byte slices:
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let document = b"[Section]\nKey = Caf\xe9";
+/// let mut parser = ini::ByteParser::new(document);
+/// assert_eq!(
+///     parser.nth(2),
+///     Some(ini::Item::Property{key: &b"Key"[..], val: Some(&b"Caf\xe9"[..]), comment: None, raw: &b"Key = Caf\xe9"[..]}));
+/// ```
+///
+/// Use [`lossy`](ByteParser::lossy) to get string items for the lines that
+/// are valid UTF-8.
+#[derive(Clone, Debug)]
+pub struct ByteParser<'a> {
+    len: usize,
+    line: u32,
+    section_ended: bool,
+    newline: Option<Newline>,
+    options: ParserOptions,
+    state: &'a [u8],
+}
+
+impl<'a> ByteParser<'a> {
+    /// Constructs a new `ByteParser` instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(s: &'a [u8]) -> Self {
+        Self::with_options(s, ParserOptions::new())
+    }
+
+    /// Constructs a new `ByteParser` instance for the given dialect.
+    #[inline]
+    #[must_use]
+    pub const fn with_options(s: &'a [u8], options: ParserOptions) -> Self {
+        ByteParser {
+            len: s.len(),
             line: 0,
             section_ended: false,
-            state,
+            newline: None,
+            options,
+            state: s,
         }
     }
 
+    /// Constructs a `ByteParser` continuing at the start of `s` with the
+    /// state of a previous parser.
+    #[inline]
+    pub(crate) const fn resume(
+        s: &'a [u8],
+        options: ParserOptions,
+        line: u32,
+        section_ended: bool,
+    ) -> Self {
+        let mut parser = Self::with_options(s, options);
+        parser.line = line;
+        parser.section_ended = section_ended;
+        parser
+    }
+
+    /// Returns the options of the parser.
+    #[inline]
+    #[must_use]
+    pub const fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
     /// Returns the line number the parser is currently at.
     #[inline]
     #[must_use]
@@ -254,44 +686,277 @@ impl<'a> Parser<'a> {
         self.line
     }
 
-    /// Returns the remainder of the input string.
+    /// Returns the byte offset in the input the parser is currently at.
     #[inline]
     #[must_use]
-    pub fn remainder(&self) -> &'a str {
-        from_utf8(self.state)
+    pub const fn offset(&self) -> usize {
+        self.len - self.state.len()
+    }
+
+    /// Returns the newline that terminated the line of the most recently
+    /// returned item (see [`Parser::newline`]).
+    #[inline]
+    #[must_use]
+    pub const fn newline(&self) -> Option<Newline> {
+        self.newline
+    }
+
+    /// Returns an iterator adapter yielding string items, reporting items
+    /// that are not valid UTF-8 as errors.
+    ///
+    /// Such items are returned as a [`ParseError`] with
+    /// [`ErrorKind::InvalidUtf8`], pointing at the first invalid byte. Parsing
+    /// continues with the next item.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let document = b"[Section]\nName = Caf\xe9\nKey = Value";
+    /// let mut iter = ini::ByteParser::new(document).lossy().skip(2);
+    /// let error = iter.next().unwrap().unwrap_err();
+    /// assert_eq!(error.kind(), ini::ErrorKind::InvalidUtf8);
+    /// assert_eq!((error.line(), error.column()), (1, 10));
+    /// assert_eq!(
+    ///     iter.next().unwrap(),
+    ///     Ok(ini::Item::Property{key: "Key", val: Some("Value"), comment: None, raw: "Key = Value"}));
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn lossy(self) -> Lossy<'a> {
+        Lossy::new(self)
+    }
+
+    /// Returns the remainder of the input.
+    #[inline]
+    #[must_use]
+    pub const fn remainder(&self) -> &'a [u8] {
+        self.state
+    }
+
+    /// Returns the state needed to resume parsing (see
+    /// [`ByteParser::resume`]).
+    #[inline]
+    pub(crate) const fn section_ended(&self) -> bool {
+        self.section_ended
     }
 
     #[inline]
     fn skip_ln(&mut self, mut s: &'a [u8]) {
-        if !s.is_empty() {
-            if s[0] == b'\r' {
-                s = &s[1..];
-            }
-            if !s.is_empty() && s[0] == b'\n' {
-                s = &s[1..];
-            }
+        self.newline = match s {
+            [b'\r', b'\n', ..] => Some(Newline::CrLf),
+            [b'\r', ..] => Some(Newline::Cr),
+            [b'\n', ..] => Some(Newline::Lf),
+            _ => None,
+        };
+        if let Some(newline) = self.newline {
+            s = &s[newline.as_str().len()..];
             self.line += 1;
         }
         self.state = s;
     }
 
-    fn get_line_and_advance(&mut self, s: &'a [u8]) -> &'a str {
+    fn get_line_and_advance(&mut self, s: &'a [u8]) -> &'a [u8] {
         let i = parse::find_nl(s);
-        let line = from_utf8(&s[..i]);
+        let line = &s[..i];
         self.skip_ln(&s[i..]);
         line
     }
+
+    /// Finds the first newline or delimiter.
+    #[inline]
+    fn find_delimiter(&self, s: &[u8]) -> usize {
+        match self.options.delimiters {
+            [] => parse::find_nl(s),
+            [chr] => parse::find_nl_chr(s, *chr),
+            chrs => parse::find_nl_chrs(s, chrs),
+        }
+    }
+
+    /// Parses a section header, with the `[` at `indent`.
+    fn section(&mut self, s: &'a [u8], indent: usize) -> Item<'a, [u8]> {
+        if !self.section_ended {
+            self.section_ended = true;
+            self.newline = None;
+            return Item::SectionEnd;
+        }
+        self.section_ended = false;
+        let i = parse::find_nl(s);
+        let raw = &s[..i];
+        let (line, comment) = self.split_inline_comment(raw);
+        let line = match comment {
+            Some(_) => line.trim_ascii_end(),
+            None => line,
+        };
+        let header = line.get(indent + 1..).unwrap_or_default();
+        let section = match header {
+            [section @ .., b']'] => section,
+            _ if self.options.require_section_end => {
+                self.skip_ln(&s[i..]);
+                return Item::Error(raw);
+            }
+            _ => match header.iter().rposition(|&chr| chr == b']') {
+                Some(end) => &header[..end],
+                None => header,
+            },
+        };
+        let section = trim(section);
+        self.skip_ln(&s[i..]);
+        Item::Section {
+            name: section,
+            comment,
+            raw,
+        }
+    }
+
+    /// Splits a line (without newline) at the start of an inline comment.
+    ///
+    /// Inline comments must be preceded by whitespace.
+    fn split_inline_comment(&self, line: &'a [u8]) -> (&'a [u8], Option<&'a [u8]>) {
+        let prefixes = self.options.inline_comment_prefixes;
+        if prefixes.is_empty() {
+            return (line, None);
+        }
+        let mut pos = 0;
+        while pos < line.len() {
+            let i = pos + parse::find_nl_chrs(&line[pos..], prefixes);
+            if i >= line.len() {
+                break;
+            }
+            if i > 0 && is_indent(line[i - 1]) {
+                return (&line[..i], Some(trim(&line[i..])));
+            }
+            pos = i + 1;
+        }
+        (line, None)
+    }
+
+    /// Parses a property (or a blank line consisting of whitespace).
+    fn property(&mut self, s: &'a [u8]) -> Item<'a, [u8]> {
+        if !self.options.inline_comment_prefixes.is_empty() {
+            return self.property_with_comment(s);
+        }
+        let eol_or_eq = self.find_delimiter(s);
+        let key = trim(&s[..eol_or_eq]);
+        if matches!(s.get(eol_or_eq), None | Some(b'\r' | b'\n')) {
+            // Key only case
+            self.skip_ln(&s[eol_or_eq..]);
+            if key.is_empty() {
+                return Item::Blank {
+                    raw: &s[..eol_or_eq],
+                };
+            }
+            Item::Property {
+                key,
+                val: None,
+                comment: None,
+                raw: &s[..eol_or_eq],
+            }
+        } else {
+            // Key + value case
+            let val_start = eol_or_eq + 1;
+
+            let mut end = val_start + parse::find_nl(&s[val_start..]);
+            if self.options.continuation != Continuation::None {
+                end = self.continuation_end(s, end);
+            }
+            let value = trim(&s[val_start..end]);
+
+            self.skip_ln(&s[end..]);
+
+            Item::Property {
+                key,
+                val: Some(value),
+                comment: None,
+                raw: &s[..end],
+            }
+        }
+    }
+
+    /// Parses a property that may have an inline comment.
+    ///
+    /// A line with only an inline comment is returned as a comment.
+    fn property_with_comment(&mut self, s: &'a [u8]) -> Item<'a, [u8]> {
+        let mut end = parse::find_nl(s);
+        let (line, comment) = self.split_inline_comment(&s[..end]);
+
+        let eol_or_eq = self.find_delimiter(line);
+        let key = trim(&line[..eol_or_eq]);
+        let has_value = eol_or_eq < line.len();
+        let val_end = match comment {
+            Some(_) => line.len(),
+            None if has_value && self.options.continuation != Continuation::None => {
+                end = self.continuation_end(s, end);
+                end
+            }
+            None => end,
+        };
+        let val = has_value.then(|| trim(&s[eol_or_eq + 1..val_end]));
+        let raw = &s[..end];
+        self.skip_ln(&s[end..]);
+
+        match (key, val, comment) {
+            ([], None, None) => Item::Blank { raw },
+            ([], None, Some(_)) => Item::Comment { raw },
+            _ => Item::Property {
+                key,
+                val,
+                comment,
+                raw,
+            },
+        }
+    }
+
+    /// Extends a value ending at `end` (the end of its first line) over any
+    /// continuation lines, returning the end of the last line.
+    fn continuation_end(&mut self, s: &[u8], mut end: usize) -> usize {
+        loop {
+            let next = match s[end..] {
+                [b'\r', b'\n', ..] => end + 2,
+                [b'\r' | b'\n', ..] => end + 1,
+                _ => return end,
+            };
+            let rest = &s[next..];
+            let continues = !rest.is_empty()
+                && match self.options.continuation {
+                    Continuation::None => false,
+                    Continuation::Indented => self.is_indented_continuation(rest),
+                    Continuation::Backslash => s[..end].last() == Some(&b'\\'),
+                };
+            if !continues {
+                return end;
+            }
+            end = next + parse::find_nl(rest);
+            self.line += 1;
+        }
+    }
+
+    /// Returns true if the line is an indented continuation line.
+    fn is_indented_continuation(&self, s: &[u8]) -> bool {
+        let options = &self.options;
+        let indent = s.iter().position(|&chr| !is_indent(chr)).unwrap_or(s.len());
+        match s.get(indent) {
+            _ if indent == 0 => false,
+            None | Some(b'\r' | b'\n') => false,
+            Some(chr) if options.indented_comments && options.comment_prefixes.contains(chr) => {
+                false
+            }
+            Some(b'[') => !options.indented_sections,
+            Some(_) => true,
+        }
+    }
 }
 
-impl<'a> Iterator for Parser<'a> {
-    type Item = Item<'a>;
+impl<'a> Iterator for ByteParser<'a> {
+    type Item = Item<'a, [u8]>;
 
-    fn next(&mut self) -> Option<Item<'a>> {
+    fn next(&mut self) -> Option<Item<'a, [u8]>> {
         let s = self.state;
+        let options = &self.options;
 
         match s.first().copied() {
             // Terminal case
             None => {
+                self.newline = None;
                 if self.section_ended {
                     None
                 } else {
@@ -305,73 +970,87 @@ impl<'a> Iterator for Parser<'a> {
                 Some(Item::Blank { raw: line })
             }
             // Comment
-            Some(b';' | b'#') => {
+            Some(chr) if options.comment_prefixes.contains(&chr) => {
                 let line = self.get_line_and_advance(s);
                 Some(Item::Comment { raw: line })
             }
             // Section
-            Some(b'[') => {
-                if self.section_ended {
-                    self.section_ended = false;
-                    let i = parse::find_nl(s);
-                    if s[i - 1] != b']' {
-                        let error = from_utf8(&s[..i]);
-                        self.skip_ln(&s[i..]);
-                        return Some(Item::Error(error));
+            Some(b'[') => Some(self.section(s, 0)),
+            // Indented comment or section
+            Some(chr)
+                if is_indent(chr) && (options.indented_comments || options.indented_sections) =>
+            {
+                let indent = s.iter().position(|&chr| !is_indent(chr)).unwrap_or(s.len());
+                match s.get(indent).copied() {
+                    Some(chr)
+                        if options.indented_comments && options.comment_prefixes.contains(&chr) =>
+                    {
+                        let line = self.get_line_and_advance(s);
+                        Some(Item::Comment { raw: line })
                     }
-                    let section = from_utf8(&s[1..i - 1]);
-                    let section = trim(section);
-                    self.skip_ln(&s[i..]);
-                    Some(Item::Section {
-                        name: section,
-                        raw: from_utf8(&s[..i]),
-                    })
-                } else {
-                    self.section_ended = true;
-                    Some(Item::SectionEnd)
+                    Some(b'[') if options.indented_sections => Some(self.section(s, indent)),
+                    _ => Some(self.property(s)),
                 }
             }
             // Property
-            _ => {
-                let eol_or_eq = parse::find_nl_chr(s, b'=');
-                let key = from_utf8(&s[..eol_or_eq]);
-                let key = trim(key);
-                if s.get(eol_or_eq) != Some(&b'=') {
-                    // Key only case
-                    self.skip_ln(&s[eol_or_eq..]);
-                    if key.is_empty() {
-                        return Some(Item::Blank {
-                            raw: from_utf8(&s[..eol_or_eq]),
-                        });
-                    }
-                    Some(Item::Property {
-                        key,
-                        val: None,
-                        raw: from_utf8(&s[..eol_or_eq]),
-                    })
-                } else {
-                    // Key + value case
-                    let val_start = &s[eol_or_eq + 1..];
+            _ => Some(self.property(s)),
+        }
+    }
+}
 
-                    let i = parse::find_nl(val_start);
-                    let value = from_utf8(&val_start[..i]);
-                    let value = trim(value);
+impl core::iter::FusedIterator for ByteParser<'_> {}
 
-                    self.skip_ln(&val_start[i..]);
+/// Iterator adapter yielding items together with their newline.
+///
+/// See [`Parser::with_newlines`].
+#[derive(Clone, Debug)]
+pub struct WithNewlines<'a> {
+    parser: Parser<'a>,
+}
 
-                    Some(Item::Property {
-                        key,
-                        val: Some(value),
-                        raw: from_utf8(&s[..eol_or_eq + i + 1]),
-                    })
-                }
-            }
-        }
+impl<'a> WithNewlines<'a> {
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &Parser<'a> {
+        &self.parser
     }
 }
 
-impl core::iter::FusedIterator for Parser<'_> {}
+impl<'a> Iterator for WithNewlines<'a> {
+    type Item = (Item<'a>, Option<Newline>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        let item = self.parser.next()?;
+        Some((item, self.parser.newline()))
+    }
+}
+
+impl core::iter::FusedIterator for WithNewlines<'_> {}
 
+mod continuation;
+#[cfg(feature = "serde")]
+pub mod de;
+#[cfg(feature = "alloc")]
+mod document;
+#[cfg(feature = "alloc")]
+mod encoding;
+mod find;
+#[cfg(feature = "alloc")]
+mod index;
+mod options;
+#[cfg(feature = "rayon")]
+pub mod par;
 mod parse;
+mod push;
+#[cfg(feature = "std")]
+mod reader;
+mod sections;
+#[cfg(feature = "serde")]
+pub mod ser;
+mod span;
+mod strict;
 #[cfg(test)]
 mod tests;
+mod writer;
@@ -0,0 +1,214 @@
+//! Configurable syntax for the parser.
+
+use crate::Continuation;
+
+/// Returns true if the bytes are ascii and contain no newlines.
+const fn is_valid_syntax(bytes: &[u8]) -> bool {
+    let mut i = 0;
+    while i < bytes.len() {
+        if !bytes[i].is_ascii() || bytes[i] == b'\n' || bytes[i] == b'\r' {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// Options controlling the INI dialect accepted by the [`Parser`].
+///
+/// The default options match the syntax described in the [`crate`]
+/// documentation. The options are const-constructible, so a dialect can be
+/// defined as a constant:
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// // Similar to Python's configparser
+/// const DIALECT: ini::ParserOptions = ini::ParserOptions::new()
+///     .delimiters(b"=:")
+///     .indented_sections(true)
+///     .indented_comments(true);
+///
+/// let mut parser = ini::Parser::with_options("  [Section]\nKey: Value", DIALECT);
+/// assert_eq!(parser.nth(1), Some(ini::Item::Section{name: "Section", comment: None, raw: "  [Section]"}));
+/// assert_eq!(
+///     parser.next(),
+///     Some(ini::Item::Property{key: "Key", val: Some("Value"), comment: None, raw: "Key: Value"}));
+/// ```
+///
+/// [`Parser`]: crate::Parser
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ParserOptions {
+    pub(crate) comment_prefixes: &'static [u8],
+    pub(crate) inline_comment_prefixes: &'static [u8],
+    pub(crate) delimiters: &'static [u8],
+    pub(crate) indented_sections: bool,
+    pub(crate) indented_comments: bool,
+    pub(crate) require_section_end: bool,
+    pub(crate) continuation: Continuation,
+}
+
+impl ParserOptions {
+    /// Constructs the default options.
+    #[inline]
+    #[must_use]
+    pub const fn new() -> Self {
+        Self {
+            comment_prefixes: b";#",
+            inline_comment_prefixes: b"",
+            delimiters: b"=",
+            indented_sections: false,
+            indented_comments: false,
+            require_section_end: true,
+            continuation: Continuation::None,
+        }
+    }
+
+    /// Sets the bytes that start a comment line (default `;` and `#`).
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the bytes are not ascii or are newlines.
+    #[inline]
+    #[must_use]
+    pub const fn comment_prefixes(mut self, prefixes: &'static [u8]) -> Self {
+        assert!(
+            is_valid_syntax(prefixes),
+            "comment prefixes must be ascii and not newlines"
+        );
+        self.comment_prefixes = prefixes;
+        self
+    }
+
+    /// Sets the bytes that start an inline comment (default none).
+    ///
+    /// An inline comment must be preceded by whitespace and extends to the end
+    /// of the line. It is split off from the value of properties and from
+    /// section headers into the `comment` field of [`Item::Property`] and
+    /// [`Item::Section`], while `raw` still covers the full line:
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let options = ini::ParserOptions::new().inline_comment_prefixes(b";#");
+    /// let mut parser = ini::Parser::with_options("[Section] # note\nKey = Value ; why", options);
+    /// assert_eq!(
+    ///     parser.nth(1),
+    ///     Some(ini::Item::Section{name: "Section", comment: Some("# note"), raw: "[Section] # note"}));
+    /// assert_eq!(
+    ///     parser.next(),
+    ///     Some(ini::Item::Property{key: "Key", val: Some("Value"), comment: Some("; why"), raw: "Key = Value ; why"}));
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the bytes are not ascii or are newlines.
+    ///
+    /// [`Item::Property`]: crate::Item::Property
+    /// [`Item::Section`]: crate::Item::Section
+    #[inline]
+    #[must_use]
+    pub const fn inline_comment_prefixes(mut self, prefixes: &'static [u8]) -> Self {
+        assert!(
+            is_valid_syntax(prefixes),
+            "inline comment prefixes must be ascii and not newlines"
+        );
+        self.inline_comment_prefixes = prefixes;
+        self
+    }
+
+    /// Sets the bytes that separate keys from values (default `=`).
+    ///
+    /// The first of these bytes on a line is the delimiter. If empty, no
+    /// property has a value.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of the bytes are not ascii or are newlines.
+    #[inline]
+    #[must_use]
+    pub const fn delimiters(mut self, delimiters: &'static [u8]) -> Self {
+        assert!(
+            is_valid_syntax(delimiters),
+            "delimiters must be ascii and not newlines"
+        );
+        self.delimiters = delimiters;
+        self
+    }
+
+    /// Sets whether section headers may be preceded by whitespace (default
+    /// false).
+    #[inline]
+    #[must_use]
+    pub const fn indented_sections(mut self, allow: bool) -> Self {
+        self.indented_sections = allow;
+        self
+    }
+
+    /// Sets whether comments may be preceded by whitespace (default false).
+    #[inline]
+    #[must_use]
+    pub const fn indented_comments(mut self, allow: bool) -> Self {
+        self.indented_comments = allow;
+        self
+    }
+
+    /// Sets whether lines starting with `[` must end with `]` (default true).
+    ///
+    /// If true, such lines are returned as [`Item::Error`] otherwise. If
+    /// false, the section name extends to the last `]` on the line (ignoring
+    /// anything after it), or to the end of the line if there is no `]`.
+    ///
+    /// [`Item::Error`]: crate::Item::Error
+    #[inline]
+    #[must_use]
+    pub const fn require_section_end(mut self, require: bool) -> Self {
+        self.require_section_end = require;
+        self
+    }
+
+    /// Sets how values continue onto following lines (default
+    /// [`Continuation::None`]).
+    ///
+    /// A continued property is returned as a single [`Item::Property`] whose
+    /// `raw` covers all the lines (including the newlines between them). The
+    /// value extends from the start of the value on the first line to the end
+    /// of the value on the last line, use [`Continuation::lines`] to iterate
+    /// over the lines of the value.
+    ///
+    /// Only properties with a value can be continued, and inline comments
+    /// are not recognized on continued lines. A property whose first line
+    /// has an inline comment is not continued.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let options = ini::ParserOptions::new().continuation(ini::Continuation::Indented);
+    /// let document = "Key = first\n  second\n\n  third";
+    ///
+    /// let mut parser = ini::Parser::with_options(document, options);
+    /// assert_eq!(
+    ///     parser.next(),
+    ///     Some(ini::Item::Property{
+    ///         key: "Key",
+    ///         val: Some("first\n  second"),
+    ///         comment: None,
+    ///         raw: "Key = first\n  second"}));
+    /// assert_eq!(parser.line(), 2);
+    /// ```
+    ///
+    /// [`Item::Property`]: crate::Item::Property
+    #[inline]
+    #[must_use]
+    pub const fn continuation(mut self, continuation: Continuation) -> Self {
+        self.continuation = continuation;
+        self
+    }
+}
+
+impl Default for ParserOptions {
+    #[inline]
+    fn default() -> Self {
+        Self::new()
+    }
+}
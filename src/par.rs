@@ -0,0 +1,169 @@
+//! Parsing large documents in parallel with rayon.
+//!
+//! The input is split into chunks at line boundaries, the chunks are parsed
+//! concurrently and the results are joined. The result is exactly what the
+//! sequential [`Parser`] (or [`Parser::spanned`]) would produce.
+//!
+//! ```
+//! use ini_roundtrip as ini;
+//!
+//! let document = "key = 1\n[Section]\nkey = 2\n";
+//! let items = ini::par::parse(document, ini::ParserOptions::new());
+//! assert_eq!(items, ini::Parser::new(document).collect::<Vec<_>>());
+//! ```
+
+use alloc::vec::Vec;
+
+use rayon::prelude::*;
+
+use crate::Continuation;
+use crate::Item;
+use crate::Parser;
+use crate::ParserOptions;
+use crate::Span;
+use crate::Spanned;
+use crate::is_indent;
+use crate::parse;
+use crate::subrange;
+
+/// Smallest chunk the input is split into.
+const MIN_CHUNK_SIZE: usize = 256 * 1024;
+
+/// Parses a document in parallel.
+///
+/// Small documents are parsed on the current thread.
+#[must_use]
+pub fn parse(s: &str, options: ParserOptions) -> Vec<Item<'_>> {
+    parse_chunks(s, options, chunk_size(s))
+}
+
+/// Parses a document in parallel, yielding the location of every item (see
+/// [`Parser::spanned`]).
+///
+/// Small documents are parsed on the current thread.
+#[must_use]
+pub fn spanned(s: &str, options: ParserOptions) -> Vec<Spanned<'_>> {
+    spanned_chunks(s, options, chunk_size(s))
+}
+
+/// Returns the chunk size used for the input.
+fn chunk_size(s: &str) -> usize {
+    // A few chunks per thread balance uneven chunks
+    (s.len() / (rayon::current_num_threads() * 4)).max(MIN_CHUNK_SIZE)
+}
+
+pub(crate) fn parse_chunks(s: &str, options: ParserOptions, chunk_size: usize) -> Vec<Item<'_>> {
+    let chunks: Vec<Vec<_>> = split(s, options, chunk_size)
+        .into_par_iter()
+        .map(|chunk| Parser::with_options(chunk, options).collect())
+        .collect();
+    join(chunks)
+}
+
+pub(crate) fn spanned_chunks(
+    s: &str,
+    options: ParserOptions,
+    chunk_size: usize,
+) -> Vec<Spanned<'_>> {
+    let mut chunks: Vec<(Vec<_>, usize, u32)> = split(s, options, chunk_size)
+        .into_par_iter()
+        .map(|chunk| {
+            let mut iter = Parser::with_options(chunk, options).spanned();
+            let items = iter.by_ref().collect();
+            (items, subrange(s, chunk).start, iter.parser().line())
+        })
+        .collect();
+
+    // Every chunk starts at the start of a line, only the offset and line of
+    // the spans need to be adjusted. Replace the number of lines of every
+    // chunk with its first line.
+    let mut line = 0;
+    for (_, _, lines) in &mut chunks {
+        let start = line;
+        line += *lines;
+        *lines = start;
+    }
+    chunks.par_iter_mut().for_each(|(items, offset, line)| {
+        for item in items {
+            shift(item, *offset, *line);
+        }
+    });
+    join(chunks.into_iter().map(|(items, _, _)| items).collect())
+}
+
+/// Moves the spans of an item parsed from a chunk starting at byte `offset`
+/// and line `line` of the input.
+fn shift(item: &mut Spanned<'_>, offset: usize, line: u32) {
+    let shift = |span: &mut Span| {
+        span.start += offset;
+        span.end += offset;
+        span.line += line;
+    };
+    shift(&mut item.span);
+    for span in [
+        &mut item.key,
+        &mut item.delimiter,
+        &mut item.value,
+        &mut item.comment,
+    ]
+    .into_iter()
+    .flatten()
+    {
+        shift(span);
+    }
+}
+
+/// Joins the items of the chunks.
+///
+/// The parser of every chunk ends with an [`Item::SectionEnd`] for the end of
+/// its input, only the one of the last chunk is the end of the document.
+fn join<T>(chunks: Vec<Vec<T>>) -> Vec<T> {
+    let len = chunks.iter().map(Vec::len).sum();
+    let last = chunks.len().saturating_sub(1);
+    let mut items = Vec::with_capacity(len);
+    for (index, mut chunk) in chunks.into_iter().enumerate() {
+        if index != last {
+            chunk.pop();
+        }
+        items.append(&mut chunk);
+    }
+    items
+}
+
+/// Splits the input into chunks of at least `chunk_size` bytes.
+fn split(s: &str, options: ParserOptions, chunk_size: usize) -> Vec<&str> {
+    let mut chunks = Vec::new();
+    let mut start = 0;
+    while s.len() - start > chunk_size {
+        let Some(end) = boundary(s.as_bytes(), start + chunk_size, options) else {
+            break;
+        };
+        chunks.push(&s[start..end]);
+        start = end;
+    }
+    chunks.push(&s[start..]);
+    chunks
+}
+
+/// Returns the start of the first line after `pos` the input can be split
+/// at.
+///
+/// A line that is not indented can only be the continuation of the previous
+/// line if that ends with a backslash. Splitting anywhere else gives the same
+/// items as parsing the whole input.
+fn boundary(s: &[u8], mut pos: usize, options: ParserOptions) -> Option<usize> {
+    let backslash = options.continuation == Continuation::Backslash;
+    loop {
+        pos += parse::find_nl(&s[pos..]);
+        let line_end = pos;
+        while matches!(s.get(pos), Some(b'\r' | b'\n')) {
+            pos += 1;
+        }
+        match s.get(pos) {
+            None => return None,
+            Some(&chr) if is_indent(chr) => {}
+            Some(_) if backslash && s[..line_end].last() == Some(&b'\\') => {}
+            Some(_) => return Some(pos),
+        }
+    }
+}
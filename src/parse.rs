@@ -1,7 +1,7 @@
 /*!
 Optimized routines for parsing INI.
 
-This module provides 2 functions: `find_nl` and `find_nl_chr`:
+This module provides 3 functions: `find_nl`, `find_nl_chr` and `find_nl_chrs`:
 
 * `fn find_nl(s: &[u8]) -> usize`
 
@@ -13,6 +13,11 @@ This module provides 2 functions: `find_nl` and `find_nl_chr`:
   Finds the first `b'\r'`, `b'\n'` or `chr` in the input byte string and returns its index.
   If no match was found returns the length of the input.
 
+* `fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize`
+
+  Finds the first `b'\r'`, `b'\n'` or any byte in `chrs` in the input byte string and returns its index.
+  If no match was found returns the length of the input.
+
 For more information on the SWAR approaches see: <http://0x80.pl/articles/simd-strfind.html#swar>.
 In reality, I only see minor improvements with SWAR (about 33% faster).
 
@@ -46,10 +51,31 @@ macro_rules! unsafe_assert {
 mod generic;
 
 cfg_if::cfg_if! {
-    // These optimizations are little endian specific
-    if #[cfg(not(target_endian = "little"))] {
+    // Only the SWAR optimizations support big endian
+    if #[cfg(all(not(target_endian = "little"), target_pointer_width = "64"))] {
+        mod swar64;
+        pub(crate) use self::swar64::*;
+    }
+    else if #[cfg(all(not(target_endian = "little"), target_pointer_width = "32"))] {
+        mod swar32;
+        pub(crate) use self::swar32::*;
+    }
+    else if #[cfg(not(target_endian = "little"))] {
         pub(crate) use self::generic::*;
     }
+    else if #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "avx512bw"))] {
+        mod avx512;
+        pub(crate) use self::avx512::*;
+    }
+    // Without AVX-512BW at compile time, detect AVX2 and AVX-512BW at runtime if std is available
+    // (even if AVX2 is enabled at compile time, AVX-512BW may still be available)
+    else if #[cfg(all(feature = "std", any(target_arch = "x86", target_arch = "x86_64"), target_feature = "sse2"))] {
+        mod avx2;
+        mod avx512;
+        mod dispatch;
+        mod sse2;
+        pub(crate) use self::dispatch::*;
+    }
     else if #[cfg(all(any(target_arch = "x86", target_arch = "x86_64"), target_feature = "avx2"))] {
         mod avx2;
         pub(crate) use self::avx2::*;
@@ -58,6 +84,14 @@ cfg_if::cfg_if! {
         mod sse2;
         pub(crate) use self::sse2::*;
     }
+    else if #[cfg(all(target_arch = "aarch64", target_feature = "neon"))] {
+        mod neon;
+        pub(crate) use self::neon::*;
+    }
+    else if #[cfg(all(target_arch = "wasm32", target_feature = "simd128"))] {
+        mod simd128;
+        pub(crate) use self::simd128::*;
+    }
     else if #[cfg(target_pointer_width = "64")] {
         mod swar64;
         pub(crate) use self::swar64::*;
@@ -80,12 +114,34 @@ fn test_parse() {
         // Check reference implementation
         assert_eq!(generic::find_nl(&buffer), i);
         assert_eq!(generic::find_nl_chr(&buffer, b'='), i);
+        assert_eq!(generic::find_nl_chrs(&buffer, b"=:"), i);
 
         // Check target implementation
         assert_eq!(find_nl(&buffer), i);
         assert_eq!(find_nl_chr(&buffer, b'='), i);
+        assert_eq!(find_nl_chrs(&buffer, b"=:"), i);
+        assert_eq!(find_nl_chrs(&buffer, b""), i);
 
         // Write annoying byte back
         buffer[i] = if i & 1 == 0 { !0x0D } else { !0x0A };
     }
 }
+
+#[test]
+fn test_parse_chrs() {
+    let mut buffer = [b'-'; 254];
+    for i in 0..buffer.len() {
+        buffer[i] = if i & 1 == 0 { b'=' } else { b':' };
+
+        // Check reference implementation
+        assert_eq!(generic::find_nl_chrs(&buffer, b"=:"), i);
+        assert_eq!(generic::find_nl_chrs(&buffer, b":="), i);
+
+        // Check target implementation
+        assert_eq!(find_nl_chrs(&buffer, b"=:"), i);
+        assert_eq!(find_nl_chrs(&buffer, b":="), i);
+        assert_eq!(find_nl_chrs(&buffer, b"*"), buffer.len());
+
+        buffer[i] = b'-';
+    }
+}
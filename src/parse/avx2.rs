@@ -12,11 +12,13 @@ use core::arch::x86_64::_mm256_or_si256;
 use core::arch::x86_64::_mm256_set1_epi8;
 
 #[inline]
+#[cfg_attr(not(target_feature = "avx2"), target_feature(enable = "avx2"))]
 pub(crate) fn find_nl(s: &[u8]) -> usize {
     let mut offset = 0;
 
     // SAFETY:
-    // * We don't build this entire module if we don't have AVX2 (see parse.rs)
+    // * AVX2 is enabled at compile time, or this is only called if it is
+    //   available at runtime (see parse.rs)
     // * The while condition ensures the pointer is in bounds.
     // * The load uses a variant that allows for unaligned loads (so that is safe).
     unsafe {
@@ -48,11 +50,13 @@ pub(crate) fn find_nl(s: &[u8]) -> usize {
 }
 
 #[inline]
+#[cfg_attr(not(target_feature = "avx2"), target_feature(enable = "avx2"))]
 pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
     let mut offset = 0;
 
     // SAFETY:
-    // * We don't build this entire module if we don't have AVX2 (see parse.rs)
+    // * AVX2 is enabled at compile time, or this is only called if it is
+    //   available at runtime (see parse.rs)
     // * The while condition ensures the pointer is in bounds.
     // * The load uses a variant that allows for unaligned loads (so that is safe).
     unsafe {
@@ -84,3 +88,45 @@ pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
     unsafe_assert!(offset <= s.len());
     offset
 }
+
+#[inline]
+#[cfg_attr(not(target_feature = "avx2"), target_feature(enable = "avx2"))]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * AVX2 is enabled at compile time, or this is only called if it is
+    //   available at runtime (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load uses a variant that allows for unaligned loads (so that is safe).
+    unsafe {
+        let n_lit = _mm256_set1_epi8(b'\n' as i8);
+        let r_lit = _mm256_set1_epi8(b'\r' as i8);
+
+        while offset + 32 <= s.len() {
+            let block = _mm256_lddqu_si256(s.as_ptr().add(offset).cast());
+
+            let n_eq = _mm256_cmpeq_epi8(n_lit, block);
+            let r_eq = _mm256_cmpeq_epi8(r_lit, block);
+            let mut eq = _mm256_or_si256(n_eq, r_eq);
+            for &chr in chrs {
+                eq = _mm256_or_si256(eq, _mm256_cmpeq_epi8(_mm256_set1_epi8(chr as i8), block));
+            }
+
+            let mask = _mm256_movemask_epi8(eq);
+
+            if mask != 0 {
+                return offset + mask.trailing_zeros() as usize;
+            }
+
+            offset += 32;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chrs(&s[offset..], chrs);
+    // SAFETY: This assert won't fail if find_nl_chrs is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
@@ -0,0 +1,126 @@
+#[cfg(target_arch = "x86")]
+use core::arch::x86::*;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::_mm512_cmpeq_epi8_mask;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::_mm512_loadu_epi8;
+#[cfg(target_arch = "x86_64")]
+use core::arch::x86_64::_mm512_set1_epi8;
+
+// The comparisons yield a mask register with one bit per byte, so no movemask
+// is needed and a block of 64 bytes is processed per iteration.
+//
+// Compared to AVX2 this is about 1.5 times as fast on long lines (2 KB values),
+// parsing short lines is dominated by the work per line and takes the same time.
+
+#[inline]
+#[cfg_attr(not(target_feature = "avx512bw"), target_feature(enable = "avx512bw"))]
+pub(crate) fn find_nl(s: &[u8]) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * AVX-512BW is enabled at compile time, or this is only called if it is
+    //   available at runtime (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load allows for unaligned loads (so that is safe).
+    unsafe {
+        let n_lit = _mm512_set1_epi8(b'\n' as i8);
+        let r_lit = _mm512_set1_epi8(b'\r' as i8);
+
+        while offset + 64 <= s.len() {
+            let block = _mm512_loadu_epi8(s.as_ptr().add(offset).cast());
+
+            let mask = _mm512_cmpeq_epi8_mask(n_lit, block) | _mm512_cmpeq_epi8_mask(r_lit, block);
+
+            if mask != 0 {
+                return offset + mask.trailing_zeros() as usize;
+            }
+
+            offset += 64;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl(&s[offset..]);
+    // SAFETY: This assert won't fail if find_nl is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+#[inline]
+#[cfg_attr(not(target_feature = "avx512bw"), target_feature(enable = "avx512bw"))]
+pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * AVX-512BW is enabled at compile time, or this is only called if it is
+    //   available at runtime (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load allows for unaligned loads (so that is safe).
+    unsafe {
+        let n_lit = _mm512_set1_epi8(b'\n' as i8);
+        let r_lit = _mm512_set1_epi8(b'\r' as i8);
+        let c_lit = _mm512_set1_epi8(chr as i8);
+
+        while offset + 64 <= s.len() {
+            let block = _mm512_loadu_epi8(s.as_ptr().add(offset).cast());
+
+            let mask = _mm512_cmpeq_epi8_mask(n_lit, block)
+                | _mm512_cmpeq_epi8_mask(r_lit, block)
+                | _mm512_cmpeq_epi8_mask(c_lit, block);
+
+            if mask != 0 {
+                return offset + mask.trailing_zeros() as usize;
+            }
+
+            offset += 64;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chr(&s[offset..], chr);
+    // SAFETY: This assert won't fail if find_nl_chr is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+#[inline]
+#[cfg_attr(not(target_feature = "avx512bw"), target_feature(enable = "avx512bw"))]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * AVX-512BW is enabled at compile time, or this is only called if it is
+    //   available at runtime (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load allows for unaligned loads (so that is safe).
+    unsafe {
+        let n_lit = _mm512_set1_epi8(b'\n' as i8);
+        let r_lit = _mm512_set1_epi8(b'\r' as i8);
+
+        while offset + 64 <= s.len() {
+            let block = _mm512_loadu_epi8(s.as_ptr().add(offset).cast());
+
+            let mut mask =
+                _mm512_cmpeq_epi8_mask(n_lit, block) | _mm512_cmpeq_epi8_mask(r_lit, block);
+            for &chr in chrs {
+                mask |= _mm512_cmpeq_epi8_mask(_mm512_set1_epi8(chr as i8), block);
+            }
+
+            if mask != 0 {
+                return offset + mask.trailing_zeros() as usize;
+            }
+
+            offset += 64;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chrs(&s[offset..], chrs);
+    // SAFETY: This assert won't fail if find_nl_chrs is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
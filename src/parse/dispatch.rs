@@ -0,0 +1,102 @@
+//! Runtime selection of the x86 backend.
+//!
+//! The backend is detected on first use and cached.
+
+use std::sync::OnceLock;
+
+use super::avx2;
+use super::avx512;
+use super::sse2;
+
+/// The routines of a backend.
+struct Backend {
+    find_nl: unsafe fn(&[u8]) -> usize,
+    find_nl_chr: unsafe fn(&[u8], u8) -> usize,
+    find_nl_chrs: unsafe fn(&[u8], &[u8]) -> usize,
+}
+
+static AVX512: Backend = Backend {
+    find_nl: avx512::find_nl,
+    find_nl_chr: avx512::find_nl_chr,
+    find_nl_chrs: avx512::find_nl_chrs,
+};
+
+static AVX2: Backend = Backend {
+    find_nl: avx2::find_nl,
+    find_nl_chr: avx2::find_nl_chr,
+    find_nl_chrs: avx2::find_nl_chrs,
+};
+
+static SSE2: Backend = Backend {
+    find_nl: sse2::find_nl,
+    find_nl_chr: sse2::find_nl_chr,
+    find_nl_chrs: sse2::find_nl_chrs,
+};
+
+static BACKEND: OnceLock<&'static Backend> = OnceLock::new();
+
+/// Returns the best backend supported by the CPU.
+fn detect() -> &'static Backend {
+    if std::is_x86_feature_detected!("avx512bw") {
+        &AVX512
+    } else if std::is_x86_feature_detected!("avx2") {
+        &AVX2
+    } else {
+        &SSE2
+    }
+}
+
+#[inline]
+fn backend() -> &'static Backend {
+    BACKEND.get_or_init(detect)
+}
+
+#[inline]
+pub(crate) fn find_nl(s: &[u8]) -> usize {
+    // SAFETY: The backend only uses features detected at runtime
+    unsafe { (backend().find_nl)(s) }
+}
+
+#[inline]
+pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
+    // SAFETY: The backend only uses features detected at runtime
+    unsafe { (backend().find_nl_chr)(s, chr) }
+}
+
+#[inline]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    // SAFETY: The backend only uses features detected at runtime
+    unsafe { (backend().find_nl_chrs)(s, chrs) }
+}
+
+#[test]
+fn test_backends() {
+    let backends = [
+        (&SSE2, true),
+        (&AVX2, std::is_x86_feature_detected!("avx2")),
+        (&AVX512, std::is_x86_feature_detected!("avx512bw")),
+    ];
+
+    // The best supported backend is selected, whatever is enabled at compile
+    // time
+    let best = backends.iter().rev().find(|(_, supported)| *supported);
+    assert!(best.is_some_and(|(best, _)| core::ptr::eq(backend(), *best)));
+
+    let mut buffer = [b'-'; 254];
+    for i in 0..buffer.len() {
+        buffer[i] = if i & 1 == 0 { b'\n' } else { b'=' };
+        let expected = super::generic::find_nl(&buffer);
+        for (backend, _) in backends.iter().filter(|(_, supported)| *supported) {
+            // SAFETY: The backend is supported by the CPU
+            unsafe {
+                assert_eq!((backend.find_nl)(&buffer), expected);
+                assert_eq!((backend.find_nl_chr)(&buffer, b'='), i);
+                assert_eq!((backend.find_nl_chrs)(&buffer, b"=:"), i);
+            }
+        }
+        assert_eq!(find_nl(&buffer), expected);
+        assert_eq!(find_nl_chr(&buffer, b'='), i);
+        assert_eq!(find_nl_chrs(&buffer, b"=:"), i);
+        buffer[i] = b'-';
+    }
+}
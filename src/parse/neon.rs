@@ -0,0 +1,135 @@
+use core::arch::aarch64::vceqq_u8;
+use core::arch::aarch64::vdupq_n_u8;
+use core::arch::aarch64::vget_lane_u64;
+use core::arch::aarch64::vld1q_u8;
+use core::arch::aarch64::vorrq_u8;
+use core::arch::aarch64::vreinterpret_u64_u8;
+use core::arch::aarch64::vreinterpretq_u16_u8;
+use core::arch::aarch64::vshrn_n_u16;
+
+// NEON has no movemask instruction. Instead the comparison result is
+// narrowed to 4 bits per byte (shift right and narrow every 16 bit lane by 4),
+// so the index of the first match is the number of trailing zeros divided by
+// 4.
+
+#[inline]
+pub(crate) fn find_nl(s: &[u8]) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * We don't build this entire module if we don't have NEON (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load has no alignment requirements.
+    unsafe {
+        let n_lit = vdupq_n_u8(b'\n');
+        let r_lit = vdupq_n_u8(b'\r');
+
+        while offset + 16 <= s.len() {
+            let block = vld1q_u8(s.as_ptr().add(offset));
+
+            let n_eq = vceqq_u8(n_lit, block);
+            let r_eq = vceqq_u8(r_lit, block);
+            let eq = vorrq_u8(n_eq, r_eq);
+
+            let mask = vget_lane_u64::<0>(vreinterpret_u64_u8(vshrn_n_u16::<4>(
+                vreinterpretq_u16_u8(eq),
+            )));
+
+            if mask != 0 {
+                return offset + (mask.trailing_zeros() >> 2) as usize;
+            }
+
+            offset += 16;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl(&s[offset..]);
+    // SAFETY: This assert won't fail if find_nl is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+#[inline]
+pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * We don't build this entire module if we don't have NEON (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load has no alignment requirements.
+    unsafe {
+        let n_lit = vdupq_n_u8(b'\n');
+        let r_lit = vdupq_n_u8(b'\r');
+        let c_lit = vdupq_n_u8(chr);
+
+        while offset + 16 <= s.len() {
+            let block = vld1q_u8(s.as_ptr().add(offset));
+
+            let n_eq = vceqq_u8(n_lit, block);
+            let r_eq = vceqq_u8(r_lit, block);
+            let c_eq = vceqq_u8(c_lit, block);
+            let eq = vorrq_u8(vorrq_u8(n_eq, r_eq), c_eq);
+
+            let mask = vget_lane_u64::<0>(vreinterpret_u64_u8(vshrn_n_u16::<4>(
+                vreinterpretq_u16_u8(eq),
+            )));
+
+            if mask != 0 {
+                return offset + (mask.trailing_zeros() >> 2) as usize;
+            }
+
+            offset += 16;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chr(&s[offset..], chr);
+    // SAFETY: This assert won't fail if find_nl_chr is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+#[inline]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    let mut offset = 0;
+
+    // SAFETY:
+    // * We don't build this entire module if we don't have NEON (see parse.rs)
+    // * The while condition ensures the pointer is in bounds.
+    // * The load has no alignment requirements.
+    unsafe {
+        let n_lit = vdupq_n_u8(b'\n');
+        let r_lit = vdupq_n_u8(b'\r');
+
+        while offset + 16 <= s.len() {
+            let block = vld1q_u8(s.as_ptr().add(offset));
+
+            let n_eq = vceqq_u8(n_lit, block);
+            let r_eq = vceqq_u8(r_lit, block);
+            let mut eq = vorrq_u8(n_eq, r_eq);
+            for &chr in chrs {
+                eq = vorrq_u8(eq, vceqq_u8(vdupq_n_u8(chr), block));
+            }
+
+            let mask = vget_lane_u64::<0>(vreinterpret_u64_u8(vshrn_n_u16::<4>(
+                vreinterpretq_u16_u8(eq),
+            )));
+
+            if mask != 0 {
+                return offset + (mask.trailing_zeros() >> 2) as usize;
+            }
+
+            offset += 16;
+        }
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chrs(&s[offset..], chrs);
+    // SAFETY: This assert won't fail if find_nl_chrs is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
@@ -0,0 +1,108 @@
+use core::arch::wasm32::u8x16_bitmask;
+use core::arch::wasm32::u8x16_eq;
+use core::arch::wasm32::u8x16_splat;
+use core::arch::wasm32::v128;
+use core::arch::wasm32::v128_load;
+use core::arch::wasm32::v128_or;
+
+#[inline]
+pub(crate) fn find_nl(s: &[u8]) -> usize {
+    let mut offset = 0;
+
+    let n_lit = u8x16_splat(b'\n');
+    let r_lit = u8x16_splat(b'\r');
+    while offset + 16 <= s.len() {
+        // SAFETY:
+        // * The pointer is in bounds by the condition in the while loop
+        // * The load has no alignment requirements
+        let block = unsafe { v128_load(s.as_ptr().add(offset).cast::<v128>()) };
+
+        let n_eq = u8x16_eq(n_lit, block);
+        let r_eq = u8x16_eq(r_lit, block);
+
+        let mask = u8x16_bitmask(v128_or(n_eq, r_eq));
+
+        if mask != 0 {
+            return offset + mask.trailing_zeros() as usize;
+        }
+
+        offset += 16;
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl(&s[offset..]);
+    // SAFETY: This assert won't fail if find_nl is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+#[inline]
+pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
+    let mut offset = 0;
+
+    let n_lit = u8x16_splat(b'\n');
+    let r_lit = u8x16_splat(b'\r');
+    let c_lit = u8x16_splat(chr);
+    while offset + 16 <= s.len() {
+        // SAFETY:
+        // * The pointer is in bounds by the condition in the while loop
+        // * The load has no alignment requirements
+        let block = unsafe { v128_load(s.as_ptr().add(offset).cast::<v128>()) };
+
+        let n_eq = u8x16_eq(n_lit, block);
+        let r_eq = u8x16_eq(r_lit, block);
+        let c_eq = u8x16_eq(c_lit, block);
+
+        let mask = u8x16_bitmask(v128_or(v128_or(n_eq, r_eq), c_eq));
+
+        if mask != 0 {
+            return offset + mask.trailing_zeros() as usize;
+        }
+
+        offset += 16;
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chr(&s[offset..], chr);
+    // SAFETY: This assert won't fail if find_nl_chr is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+#[inline]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    let mut offset = 0;
+
+    let n_lit = u8x16_splat(b'\n');
+    let r_lit = u8x16_splat(b'\r');
+    while offset + 16 <= s.len() {
+        // SAFETY:
+        // * The pointer is in bounds by the condition in the while loop
+        // * The load has no alignment requirements
+        let block = unsafe { v128_load(s.as_ptr().add(offset).cast::<v128>()) };
+
+        let n_eq = u8x16_eq(n_lit, block);
+        let r_eq = u8x16_eq(r_lit, block);
+        let mut eq = v128_or(n_eq, r_eq);
+        for &chr in chrs {
+            eq = v128_or(eq, u8x16_eq(u8x16_splat(chr), block));
+        }
+
+        let mask = u8x16_bitmask(eq);
+
+        if mask != 0 {
+            return offset + mask.trailing_zeros() as usize;
+        }
+
+        offset += 16;
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chrs(&s[offset..], chrs);
+    // SAFETY: This assert won't fail if find_nl_chrs is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
@@ -11,7 +11,7 @@ pub(crate) fn find_nl(s: &[u8]) -> usize {
         let word = unsafe { s.as_ptr().add(offset).cast::<u32>().read_unaligned() };
         let mask = cmpeq(n_lit, word) | cmpeq(r_lit, word);
         if mask != 0 {
-            return offset + (mask.trailing_zeros() >> 3) as usize;
+            return offset + first_match(mask);
         }
 
         offset += 4;
@@ -39,7 +39,7 @@ pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
         let word = unsafe { s.as_ptr().add(offset).cast::<u32>().read_unaligned() };
         let mask = cmpeq(n_lit, word) | cmpeq(r_lit, word) | cmpeq(c_lit, word);
         if mask != 0 {
-            return offset + (mask.trailing_zeros() >> 3) as usize;
+            return offset + first_match(mask);
         }
 
         offset += 4;
@@ -53,6 +53,48 @@ pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
     offset
 }
 
+#[inline]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    let mut offset = 0;
+
+    let n_lit = b'\n' as u32 * 0x01010101u32;
+    let r_lit = b'\r' as u32 * 0x01010101u32;
+    while offset + 4 <= s.len() {
+        // SAFETY:
+        // * The pointer is in bounds by the condition in the while loop
+        // * We use read_unaligned, so alignment is not a concern
+        let word = unsafe { s.as_ptr().add(offset).cast::<u32>().read_unaligned() };
+        let mut mask = cmpeq(n_lit, word) | cmpeq(r_lit, word);
+        for &chr in chrs {
+            mask |= cmpeq(chr as u32 * 0x01010101u32, word);
+        }
+        if mask != 0 {
+            return offset + first_match(mask);
+        }
+
+        offset += 4;
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chrs(&s[offset..], chrs);
+    // SAFETY: This assert won't fail if find_nl_chrs is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+/// Returns the index of the first matching byte in a non-zero mask from
+/// `cmpeq` (see `swar64.rs`).
+#[inline]
+fn first_match(mask: u32) -> usize {
+    #[cfg(target_endian = "little")]
+    return (mask.trailing_zeros() >> 3) as usize;
+    #[cfg(target_endian = "big")]
+    return (mask.leading_zeros() >> 3) as usize;
+}
+
+/// Sets the high bit of every byte that is equal in both words (see
+/// `swar64.rs`).
 #[inline]
 fn cmpeq(needle: u32, haystack: u32) -> u32 {
     let neq = !(needle ^ haystack);
@@ -11,7 +11,7 @@ pub(crate) fn find_nl(s: &[u8]) -> usize {
         let word = unsafe { s.as_ptr().add(offset).cast::<u64>().read_unaligned() };
         let mask = cmpeq(n_lit, word) | cmpeq(r_lit, word);
         if mask != 0 {
-            return offset + (mask.trailing_zeros() >> 3) as usize;
+            return offset + first_match(mask);
         }
 
         offset += 8;
@@ -39,7 +39,7 @@ pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
         let word = unsafe { s.as_ptr().add(offset).cast::<u64>().read_unaligned() };
         let mask = cmpeq(n_lit, word) | cmpeq(r_lit, word) | cmpeq(c_lit, word);
         if mask != 0 {
-            return offset + (mask.trailing_zeros() >> 3) as usize;
+            return offset + first_match(mask);
         }
 
         offset += 8;
@@ -53,6 +53,54 @@ pub(crate) fn find_nl_chr(s: &[u8], chr: u8) -> usize {
     offset
 }
 
+#[inline]
+pub(crate) fn find_nl_chrs(s: &[u8], chrs: &[u8]) -> usize {
+    let mut offset = 0;
+
+    let n_lit = b'\n' as u64 * 0x0101010101010101u64;
+    let r_lit = b'\r' as u64 * 0x0101010101010101u64;
+    while offset + 8 <= s.len() {
+        // SAFETY:
+        // * The pointer is in bounds by the condition in the while loop
+        // * We use read_unaligned, so alignment is not a concern
+        let word = unsafe { s.as_ptr().add(offset).cast::<u64>().read_unaligned() };
+        let mut mask = cmpeq(n_lit, word) | cmpeq(r_lit, word);
+        for &chr in chrs {
+            mask |= cmpeq(chr as u64 * 0x0101010101010101u64, word);
+        }
+        if mask != 0 {
+            return offset + first_match(mask);
+        }
+
+        offset += 8;
+    }
+
+    // SAFETY: This assert won't fail if the code above is correct
+    unsafe_assert!(offset <= s.len());
+    offset += super::generic::find_nl_chrs(&s[offset..], chrs);
+    // SAFETY: This assert won't fail if find_nl_chrs is correct (which we assume)
+    unsafe_assert!(offset <= s.len());
+    offset
+}
+
+/// Returns the index of the first matching byte in a non-zero mask from
+/// `cmpeq`.
+///
+/// The first byte in memory is the least significant byte of the word on
+/// little endian, but the most significant byte on big endian.
+#[inline]
+fn first_match(mask: u64) -> usize {
+    #[cfg(target_endian = "little")]
+    return (mask.trailing_zeros() >> 3) as usize;
+    #[cfg(target_endian = "big")]
+    return (mask.leading_zeros() >> 3) as usize;
+}
+
+/// Sets the high bit of every byte that is equal in both words.
+///
+/// Unlike the common `haszero` trick there are no carries between the bytes,
+/// so there are no false positives after the first match (which matters on big
+/// endian, where the first match is the most significant byte).
 #[inline]
 fn cmpeq(needle: u64, haystack: u64) -> u64 {
     let neq = !(needle ^ haystack);
@@ -0,0 +1,215 @@
+//! Push-based parsing of chunked input.
+
+use core::fmt;
+use core::str;
+
+use crate::ByteParser;
+use crate::Item;
+use crate::Newline;
+use crate::ParserOptions;
+use crate::from_utf8;
+use crate::parse;
+use crate::strict;
+
+/// Error returned by [`PushParser`].
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum PushError {
+    /// The buffer is too small to hold an item and the line after it.
+    BufferFull,
+    /// The input is not valid UTF-8.
+    InvalidUtf8,
+}
+
+impl fmt::Display for PushError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(match self {
+            Self::BufferFull => "line does not fit in the buffer",
+            Self::InvalidUtf8 => "invalid UTF-8",
+        })
+    }
+}
+
+impl core::error::Error for PushError {}
+
+/// Ini parser for input arriving in chunks.
+///
+/// Instead of pulling items from a complete document like [`Parser`], input
+/// is pushed into the parser in chunks of any size, and every item that is
+/// complete is passed to a callback together with its newline (see
+/// [`Parser::with_newlines`]). The items are the same as returned by
+/// [`Parser`], including [`Item::SectionEnd`].
+///
+/// The input is collected in a caller-provided buffer, no allocation is
+/// performed. The buffer must be large enough to hold the longest item
+/// together with the line after it (which the parser needs to look ahead at),
+/// otherwise [`PushError::BufferFull`] is returned.
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let mut buf = [0; 64];
+/// let mut parser = ini::PushParser::new(&mut buf);
+///
+/// let mut count = 0;
+/// let mut on_item = |item: ini::Item<'_>, _newline: Option<ini::Newline>| {
+///     if let ini::Item::Property{key, val, ..} = item {
+///         assert_eq!((key, val), ("Key", Some("Value")));
+///         count += 1;
+///     }
+/// };
+/// for chunk in [&b"[SECT"[..], b"ION]\nKe", b"y = Val", b"ue\n"] {
+///     parser.push(chunk, &mut on_item).unwrap();
+/// }
+/// parser.finish(&mut on_item).unwrap();
+/// assert_eq!(count, 1);
+/// ```
+///
+/// After an error, the parser must not be used anymore.
+#[derive(Debug)]
+pub struct PushParser<'b> {
+    buf: &'b mut [u8],
+    /// Start of the unparsed input in the buffer
+    start: usize,
+    /// End of the valid UTF-8 in the buffer
+    valid: usize,
+    /// End of the input in the buffer
+    len: usize,
+    invalid: bool,
+    line: u32,
+    section_ended: bool,
+    options: ParserOptions,
+}
+
+impl<'b> PushParser<'b> {
+    /// Constructs a new `PushParser` using the given buffer.
+    #[inline]
+    #[must_use]
+    pub const fn new(buf: &'b mut [u8]) -> Self {
+        Self::with_options(buf, ParserOptions::new())
+    }
+
+    /// Constructs a new `PushParser` using the given buffer and dialect.
+    #[inline]
+    #[must_use]
+    pub const fn with_options(buf: &'b mut [u8], options: ParserOptions) -> Self {
+        Self {
+            buf,
+            start: 0,
+            valid: 0,
+            len: 0,
+            invalid: false,
+            line: 0,
+            section_ended: false,
+            options,
+        }
+    }
+
+    /// Returns the options of the parser.
+    #[inline]
+    #[must_use]
+    pub const fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Returns the line number the parser is currently at.
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Pushes a chunk of input, calling `f` for every item that is complete.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the buffer is full or the input is not valid
+    /// UTF-8. Invalid UTF-8 is reported once the parser reaches the item
+    /// containing it, all items before it are still passed to `f`.
+    pub fn push<F>(&mut self, mut chunk: &[u8], mut f: F) -> Result<(), PushError>
+    where
+        F: FnMut(Item<'_>, Option<Newline>),
+    {
+        loop {
+            self.emit(false, &mut f)?;
+            if chunk.is_empty() {
+                return Ok(());
+            }
+            if self.start > 0 {
+                self.buf.copy_within(self.start..self.len, 0);
+                self.valid -= self.start;
+                self.len -= self.start;
+                self.start = 0;
+            }
+            let n = chunk.len().min(self.buf.len() - self.len);
+            if n == 0 {
+                return Err(PushError::BufferFull);
+            }
+            self.buf[self.len..self.len + n].copy_from_slice(&chunk[..n]);
+            self.len += n;
+            chunk = &chunk[n..];
+
+            if self.invalid {
+                continue;
+            }
+            match str::from_utf8(&self.buf[self.valid..self.len]) {
+                Ok(_) => self.valid = self.len,
+                Err(err) => {
+                    self.valid += err.valid_up_to();
+                    // An incomplete character at the end may be completed later
+                    self.invalid = err.error_len().is_some();
+                }
+            }
+        }
+    }
+
+    /// Signals the end of the input, calling `f` for the remaining items.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the remaining input is not valid UTF-8, including
+    /// when it ends with an incomplete UTF-8 character.
+    pub fn finish<F>(mut self, mut f: F) -> Result<(), PushError>
+    where
+        F: FnMut(Item<'_>, Option<Newline>),
+    {
+        self.invalid |= self.valid < self.len;
+        self.emit(true, &mut f)
+    }
+
+    /// Passes all complete items in the buffer to `f`.
+    fn emit<F>(&mut self, eof: bool, f: &mut F) -> Result<(), PushError>
+    where
+        F: FnMut(Item<'_>, Option<Newline>),
+    {
+        loop {
+            // Once invalid UTF-8 was found, the rest of the input is parsed as
+            // bytes so the items before the invalid data can be completed
+            let end = if self.invalid { self.len } else { self.valid };
+            let bytes = &self.buf[self.start..end];
+            let mut parser = ByteParser::resume(bytes, self.options, self.line, self.section_ended);
+            let Some(item) = parser.next() else {
+                return Ok(());
+            };
+            // The parser looks ahead at the line after an item. An item
+            // containing invalid data is an error whatever follows it.
+            let rest = parser.remainder();
+            let complete = eof
+                || parse::find_nl(rest) < rest.len()
+                || self.start + parser.offset() > self.valid;
+            if !complete {
+                return Ok(());
+            }
+            let item = if self.invalid {
+                strict::check_utf8(item, self.line).map_err(|_| PushError::InvalidUtf8)?
+            } else {
+                // The buffer was validated up to `valid` when it was filled
+                item.map(from_utf8)
+            };
+            self.start += parser.offset();
+            self.line = parser.line();
+            self.section_ended = parser.section_ended();
+            f(item, parser.newline());
+        }
+    }
+}
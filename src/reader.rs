@@ -0,0 +1,276 @@
+//! Parsing from [`std::io`] readers.
+
+use core::str;
+use std::io;
+use std::io::BufRead;
+use std::vec::Vec;
+
+use crate::ByteParser;
+use crate::Item;
+use crate::Newline;
+use crate::ParserOptions;
+use crate::from_utf8;
+use crate::parse;
+use crate::strict;
+
+/// Ini streaming parser reading from a [`BufRead`].
+///
+/// Unlike [`Parser`] the document does not need to be in memory as a whole.
+/// Input is read into an internal buffer as needed, which only holds the
+/// current item and the line after it (plus whatever was read along with
+/// them). Items borrow from this buffer, so this is a lending iterator: each
+/// item must be dropped before calling [`next`](ReaderParser::next) again.
+/// Items are returned with the same semantics as [`Parser`].
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let document = "[SECTION]\nKey = Value\n";
+/// let mut parser = ini::ReaderParser::new(document.as_bytes());
+///
+/// let mut keys = Vec::new();
+/// while let Some(item) = parser.next() {
+///     if let ini::Item::Property{key, ..} = item.unwrap() {
+///         keys.push(key.to_string());
+///     }
+/// }
+/// assert_eq!(keys, ["Key"]);
+/// ```
+///
+/// Owned data can be collected from the items, e.g. a [`Document`] can be
+/// built using its `Extend` implementation:
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let document = "[SECTION]\r\nKey = Value";
+/// let mut parser = ini::ReaderParser::new(document.as_bytes());
+///
+/// let mut doc = ini::Document::new();
+/// while let Some(line) = parser.next_with_newline() {
+///     doc.extend([line.unwrap()]);
+/// }
+/// assert_eq!(doc.to_string(), document);
+/// ```
+///
+/// Requires the `std` feature.
+///
+/// [`Document`]: crate::Document
+#[derive(Debug)]
+pub struct ReaderParser<R> {
+    reader: R,
+    buf: Vec<u8>,
+    /// Start of the unparsed input in the buffer
+    start: usize,
+    /// End of the valid UTF-8 in the buffer
+    valid: usize,
+    /// End of the input searched for newlines by `is_complete`
+    scanned: usize,
+    eof: bool,
+    done: bool,
+    /// Whether the valid part of the buffer is followed by invalid UTF-8
+    invalid: bool,
+    line: u32,
+    section_ended: bool,
+    newline: Option<Newline>,
+    options: ParserOptions,
+}
+
+impl<R: BufRead> ReaderParser<R> {
+    /// Constructs a new `ReaderParser` instance.
+    #[inline]
+    #[must_use]
+    pub const fn new(reader: R) -> Self {
+        Self::with_options(reader, ParserOptions::new())
+    }
+
+    /// Constructs a new `ReaderParser` instance for the given dialect.
+    #[inline]
+    #[must_use]
+    pub const fn with_options(reader: R, options: ParserOptions) -> Self {
+        Self {
+            reader,
+            buf: Vec::new(),
+            start: 0,
+            valid: 0,
+            scanned: 0,
+            eof: false,
+            done: false,
+            invalid: false,
+            line: 0,
+            section_ended: false,
+            newline: None,
+            options,
+        }
+    }
+
+    /// Returns the next item.
+    ///
+    /// Returns an error if reading fails or the input is not valid UTF-8.
+    /// Invalid UTF-8 is reported once the parser reaches the item containing
+    /// it (like [`ByteParser::lossy`]), as an [`io::ErrorKind::InvalidData`]
+    /// error wrapping a [`ParseError`] with the location of the first invalid
+    /// byte in the document. After an error, `None` is returned.
+    ///
+    /// [`ParseError`]: crate::ParseError
+    #[expect(clippy::should_implement_trait, reason = "lending iterator")]
+    pub fn next(&mut self) -> Option<io::Result<Item<'_>>> {
+        let line = self.next_with_newline()?;
+        Some(line.map(|(item, _)| item))
+    }
+
+    /// Returns the next item together with the newline that terminated its
+    /// line.
+    ///
+    /// This is the equivalent of [`Parser::with_newlines`], see
+    /// [`next`](ReaderParser::next) for details.
+    pub fn next_with_newline(&mut self) -> Option<io::Result<(Item<'_>, Option<Newline>)>> {
+        if self.done {
+            return None;
+        }
+        if self.start * 2 >= self.buf.len() {
+            self.buf.drain(..self.start);
+            self.valid -= self.start;
+            self.scanned -= self.start;
+            self.start = 0;
+        }
+        while !self.is_complete() {
+            if let Err(err) = self.fill() {
+                self.done = true;
+                return Some(Err(err));
+            }
+        }
+        // Borrow only the buffer, the rest of the state is updated below
+        let end = self.end();
+        let bytes = &self.buf[self.start..end];
+        let mut parser = ByteParser::resume(bytes, self.options, self.line, self.section_ended);
+        let item = parser.next()?;
+        let item = if self.invalid {
+            strict::check_utf8(item, self.line)
+        } else {
+            // The buffer was validated up to `valid` when it was filled
+            Ok(item.map(from_utf8))
+        };
+        let item = match item {
+            Ok(item) => item,
+            Err(err) => {
+                self.done = true;
+                return Some(Err(io::Error::new(io::ErrorKind::InvalidData, err)));
+            }
+        };
+        self.start += parser.offset();
+        self.scanned = self.start;
+        self.line = parser.line();
+        self.section_ended = parser.section_ended();
+        self.newline = parser.newline();
+        Some(Ok((item, parser.newline())))
+    }
+
+    /// Returns the end of the buffered input that can be parsed.
+    ///
+    /// Once invalid UTF-8 was found, the rest of the input is parsed as bytes
+    /// so the items before the invalid data can be completed.
+    fn end(&self) -> usize {
+        if self.invalid {
+            self.buf.len()
+        } else {
+            self.valid
+        }
+    }
+
+    /// Returns true if the buffer contains the complete next item.
+    ///
+    /// This is the case if the line after the item is complete as well (the
+    /// parser needs to look ahead for newlines and continuation lines), or if
+    /// the end of the input has been reached.
+    fn is_complete(&mut self) -> bool {
+        if self.eof {
+            return true;
+        }
+        // Only a newline can complete the item, skip parsing the buffer
+        // again (which is quadratic for long lines) until one is read
+        let end = self.end();
+        let new = &self.buf[self.scanned..end];
+        let found = parse::find_nl(new) < new.len();
+        self.scanned = end;
+        if !found {
+            return false;
+        }
+        let bytes = &self.buf[self.start..end];
+        let mut parser = ByteParser::resume(bytes, self.options, self.line, self.section_ended);
+        if parser.next().is_none() {
+            return false;
+        }
+        // An item containing invalid data is an error whatever follows it
+        let rest = parser.remainder();
+        parse::find_nl(rest) < rest.len() || self.start + parser.offset() > self.valid
+    }
+
+    /// Reads more input into the buffer, validating it.
+    fn fill(&mut self) -> io::Result<()> {
+        let data = loop {
+            match self.reader.fill_buf() {
+                Ok(data) => break data,
+                Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+                Err(err) => return Err(err),
+            }
+        };
+        let len = data.len();
+        self.buf.extend_from_slice(data);
+        self.reader.consume(len);
+        self.eof = len == 0;
+
+        if self.invalid {
+            return Ok(());
+        }
+        match str::from_utf8(&self.buf[self.valid..]) {
+            Ok(_) => self.valid = self.buf.len(),
+            Err(err) => {
+                self.valid += err.valid_up_to();
+                // An incomplete character at the end may be completed later
+                self.invalid = err.error_len().is_some() || self.eof;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<R> ReaderParser<R> {
+    /// Returns the options of the parser.
+    #[inline]
+    #[must_use]
+    pub const fn options(&self) -> &ParserOptions {
+        &self.options
+    }
+
+    /// Returns the line number the parser is currently at.
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the newline that terminated the line of the most recently
+    /// returned item (see [`Parser::newline`]).
+    #[inline]
+    #[must_use]
+    pub const fn newline(&self) -> Option<Newline> {
+        self.newline
+    }
+
+    /// Returns a reference to the underlying reader.
+    #[inline]
+    #[must_use]
+    pub const fn get_ref(&self) -> &R {
+        &self.reader
+    }
+
+    /// Consumes the parser, returning the underlying reader.
+    ///
+    /// Any buffered input is lost.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> R {
+        self.reader
+    }
+}
@@ -0,0 +1,227 @@
+//! Grouping items by section.
+
+use crate::Item;
+use crate::Parser;
+
+/// A section of a document.
+///
+/// This is a view into the input, the items of the section are parsed when
+/// iterating over them. See [`Parser::sections`].
+#[derive(Clone, Debug)]
+pub struct Section<'a> {
+    header: Option<Item<'a>>,
+    body: Parser<'a>,
+}
+
+impl<'a> Section<'a> {
+    /// Returns the header of the section.
+    ///
+    /// This is an [`Item::Section`], an [`Item::Error`] for a malformed
+    /// header, or `None` for the implicit section before the first header.
+    #[inline]
+    #[must_use]
+    pub const fn header(&self) -> Option<Item<'a>> {
+        self.header
+    }
+
+    /// Returns the name of the section.
+    ///
+    /// This is `None` for the implicit section before the first header and
+    /// for malformed headers.
+    #[inline]
+    #[must_use]
+    pub const fn name(&self) -> Option<&'a str> {
+        match self.header {
+            Some(Item::Section { name, .. }) => Some(name),
+            _ => None,
+        }
+    }
+
+    /// Returns the raw line of the header, `None` for the implicit section
+    /// before the first header.
+    #[inline]
+    #[must_use]
+    pub const fn raw(&self) -> Option<&'a str> {
+        match self.header {
+            Some(header) => header.raw(),
+            None => None,
+        }
+    }
+
+    /// Returns the line of the first item after the header.
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.body.line()
+    }
+
+    /// Returns an iterator over the items of the section (properties,
+    /// comments and blank lines), not including the header.
+    #[inline]
+    #[must_use]
+    pub fn items(&self) -> SectionItems<'a> {
+        SectionItems {
+            parser: self.body.clone(),
+            done: false,
+        }
+    }
+
+    /// Returns an iterator over the keys and values of the properties of the
+    /// section.
+    #[inline]
+    pub fn properties(&self) -> impl Iterator<Item = (&'a str, Option<&'a str>)> + use<'a> {
+        self.items().filter_map(|item| match item {
+            Item::Property { key, val, .. } => Some((key, val)),
+            _ => None,
+        })
+    }
+
+    /// Returns the value of the first property with the given key.
+    ///
+    /// Returns `None` if there is no such property or it has no value.
+    #[must_use]
+    pub fn get(&self, key: &str) -> Option<&'a str> {
+        self.properties()
+            .find(|&(other, _)| other == key)
+            .and_then(|(_, val)| val)
+    }
+}
+
+/// Iterator over the items of a [`Section`].
+///
+/// See [`Section::items`].
+#[derive(Clone, Debug)]
+pub struct SectionItems<'a> {
+    parser: Parser<'a>,
+    done: bool,
+}
+
+impl<'a> SectionItems<'a> {
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &Parser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for SectionItems<'a> {
+    type Item = Item<'a>;
+
+    fn next(&mut self) -> Option<Item<'a>> {
+        if self.done {
+            return None;
+        }
+        match self.parser.next() {
+            None | Some(Item::SectionEnd) => {
+                self.done = true;
+                None
+            }
+            item => item,
+        }
+    }
+}
+
+impl core::iter::FusedIterator for SectionItems<'_> {}
+
+/// Iterator adapter yielding the sections of a document.
+///
+/// See [`Parser::sections`].
+#[derive(Clone, Debug)]
+pub struct Sections<'a> {
+    parser: Parser<'a>,
+    started: bool,
+}
+
+impl<'a> Sections<'a> {
+    #[inline]
+    pub(crate) const fn new(parser: Parser<'a>) -> Self {
+        Self {
+            parser,
+            started: false,
+        }
+    }
+
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &Parser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for Sections<'a> {
+    type Item = Section<'a>;
+
+    fn next(&mut self) -> Option<Section<'a>> {
+        if !self.started {
+            self.started = true;
+            return Some(Section {
+                header: None,
+                body: self.parser.clone(),
+            });
+        }
+        // Skip the body of the previous section
+        while self.parser.next()? != Item::SectionEnd {}
+        let header = self.parser.next()?;
+        Some(Section {
+            header: Some(header),
+            body: self.parser.clone(),
+        })
+    }
+}
+
+impl core::iter::FusedIterator for Sections<'_> {}
+
+/// Iterator adapter yielding items together with the name of their section.
+///
+/// See [`Parser::with_section`].
+#[derive(Clone, Debug)]
+pub struct WithSection<'a> {
+    parser: Parser<'a>,
+    section: Option<&'a str>,
+}
+
+impl<'a> WithSection<'a> {
+    #[inline]
+    pub(crate) const fn new(parser: Parser<'a>) -> Self {
+        Self {
+            parser,
+            section: None,
+        }
+    }
+
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &Parser<'a> {
+        &self.parser
+    }
+
+    /// Returns the name of the current section.
+    #[inline]
+    #[must_use]
+    pub const fn section(&self) -> Option<&'a str> {
+        self.section
+    }
+}
+
+impl<'a> Iterator for WithSection<'a> {
+    type Item = (Option<&'a str>, Item<'a>);
+
+    #[inline]
+    fn next(&mut self) -> Option<Self::Item> {
+        // After a section end the next item is always a section header
+        let header = self.parser.section_ended();
+        let item = self.parser.next()?;
+        if header {
+            self.section = match item {
+                Item::Section { name, .. } => Some(name),
+                _ => None,
+            };
+        }
+        Some((self.section, item))
+    }
+}
+
+impl core::iter::FusedIterator for WithSection<'_> {}
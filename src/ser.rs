@@ -0,0 +1,498 @@
+//! Serializing into ini documents with serde.
+
+use alloc::string::String;
+use alloc::string::ToString;
+use core::fmt;
+
+use serde::ser;
+use serde::ser::Impossible;
+use serde::ser::Serialize;
+
+use crate::Document;
+
+/// Error returned when serializing fails.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct Error {
+    msg: String,
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.msg)
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<T: fmt::Display>(msg: T) -> Self {
+        Self {
+            msg: msg.to_string(),
+        }
+    }
+}
+
+/// Returns an error for a value that cannot be represented.
+fn unsupported(what: &str) -> Error {
+    ser::Error::custom(format_args!("{what} are not supported"))
+}
+
+/// Updates a document in place from a serializable value.
+///
+/// See [`Serializer`] for how the value is mapped.
+///
+/// # Errors
+///
+/// Returns an error if the value cannot be represented as an ini document.
+/// The document may have been partially updated in that case.
+#[inline]
+pub fn to_document<T: Serialize + ?Sized>(document: &mut Document, value: &T) -> Result<(), Error> {
+    value.serialize(Serializer::new(document))
+}
+
+/// Serde serializer updating an existing [`Document`].
+///
+/// The value must be a struct or map. Fields with a struct or map value are
+/// sections, all other fields are properties (before the first section if at
+/// the top level). This is the inverse of the [`Deserializer`].
+///
+/// Only properties whose value changed are modified (using
+/// [`Document::set`], which preserves the formatting of the line), new
+/// properties are added at the end of their section and new sections at the
+/// end of the document. All other lines, including comments and properties
+/// not present in the value, are left untouched:
+///
+/// ```
+/// use ini_roundtrip as ini;
+/// use serde::Serialize;
+///
+/// #[derive(Serialize)]
+/// struct Config<'a> {
+///     name: &'a str,
+///     server: Server<'a>,
+/// }
+///
+/// #[derive(Serialize)]
+/// struct Server<'a> {
+///     host: &'a str,
+///     port: u16,
+/// }
+///
+/// let mut document = ini::Document::parse("\
+/// name = example
+///
+/// [server]
+/// ; The host to connect to
+/// host   = localhost
+/// extra  = unknown
+/// ");
+///
+/// let config = Config { name: "example", server: Server { host: "example.com", port: 8080 } };
+/// ini::ser::to_document(&mut document, &config).unwrap();
+/// assert_eq!(document.to_string(), "\
+/// name = example
+///
+/// [server]
+/// ; The host to connect to
+/// host   = example.com
+/// extra  = unknown
+/// port=8080
+/// ");
+/// ```
+///
+/// Strings, numbers, booleans, chars and unit enum variants are written using
+/// their string representation. `None` leaves the property untouched, as does
+/// `true` for a property without a value (which the deserializer reads as
+/// `true`). Sequences, nested sections and other compound values are not
+/// supported, neither are values that cannot be written on a single line
+/// (such as strings containing newlines).
+///
+/// Requires the `serde` feature.
+///
+/// [`Deserializer`]: crate::de::Deserializer
+#[derive(Debug)]
+pub struct Serializer<'a> {
+    document: &'a mut Document,
+}
+
+impl<'a> Serializer<'a> {
+    /// Constructs a new `Serializer` updating the given document.
+    #[inline]
+    #[must_use]
+    pub const fn new(document: &'a mut Document) -> Self {
+        Self { document }
+    }
+}
+
+/// Implements serializing methods that always fail.
+macro_rules! serialize_unsupported {
+    ($what:literal; $($method:ident($($arg:ty),*) -> $ok:ty;)*) => {$(
+        fn $method(self, $(_: $arg),*) -> Result<$ok, Error> {
+            Err(unsupported($what))
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for Serializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = TableSerializer<'a>;
+    type SerializeStruct = TableSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    serialize_unsupported! {
+        "documents other than structs and maps";
+        serialize_bool(bool) -> ();
+        serialize_i8(i8) -> ();
+        serialize_i16(i16) -> ();
+        serialize_i32(i32) -> ();
+        serialize_i64(i64) -> ();
+        serialize_u8(u8) -> ();
+        serialize_u16(u16) -> ();
+        serialize_u32(u32) -> ();
+        serialize_u64(u64) -> ();
+        serialize_f32(f32) -> ();
+        serialize_f64(f64) -> ();
+        serialize_char(char) -> ();
+        serialize_str(&str) -> ();
+        serialize_bytes(&[u8]) -> ();
+        serialize_none() -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_unit_variant(&'static str, u32, &'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported("documents other than structs and maps"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<TableSerializer<'a>, Error> {
+        Ok(TableSerializer::new(self.document, None))
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<TableSerializer<'a>, Error> {
+        Ok(TableSerializer::new(self.document, None))
+    }
+}
+
+/// Serializer for the fields of the document or of a section.
+///
+/// Returned when serializing a struct or map with [`Serializer`].
+#[derive(Debug)]
+pub struct TableSerializer<'a> {
+    document: &'a mut Document,
+    section: Option<String>,
+    key: Option<String>,
+}
+
+impl<'a> TableSerializer<'a> {
+    const fn new(document: &'a mut Document, section: Option<String>) -> Self {
+        Self {
+            document,
+            section,
+            key: None,
+        }
+    }
+
+    /// Serializes the value of a field.
+    fn field<T: Serialize + ?Sized>(&mut self, key: &str, value: &T) -> Result<(), Error> {
+        value.serialize(FieldSerializer {
+            document: self.document,
+            section: self.section.as_deref(),
+            key,
+        })
+    }
+}
+
+impl ser::SerializeStruct for TableSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl ser::SerializeMap for TableSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: Serialize + ?Sized>(&mut self, key: &T) -> Result<(), Error> {
+        self.key = Some(key.serialize(KeySerializer)?);
+        Ok(())
+    }
+
+    fn serialize_value<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| ser::Error::custom("value serialized before key"))?;
+        self.field(&key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Serializer for the value of a single field.
+struct FieldSerializer<'a> {
+    document: &'a mut Document,
+    section: Option<&'a str>,
+    key: &'a str,
+}
+
+impl FieldSerializer<'_> {
+    /// Sets the property, unless it already has the value.
+    fn set(self, value: &str) -> Result<(), Error> {
+        self.document
+            .check_property(self.key, value)
+            .map_err(|msg| ser::Error::custom(format_args!("property {:?}: {msg}", self.key)))?;
+        let current = self.document.get(self.section, self.key);
+        let unchanged = match current {
+            Some(current) => current == value,
+            // A property without a value reads as `true`
+            None => value == "true" && self.document.contains_key(self.section, self.key),
+        };
+        if !unchanged {
+            self.document.set(self.section, self.key, value);
+        }
+        Ok(())
+    }
+}
+
+/// Implements serializing a value using its string representation.
+macro_rules! serialize_display {
+    ($($method:ident($ty:ty);)*) => {$(
+        fn $method(self, value: $ty) -> Result<Self::Ok, Error> {
+            self.set(&value.to_string())
+        }
+    )*};
+}
+
+impl<'a> ser::Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Impossible<(), Error>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = TableSerializer<'a>;
+    type SerializeStruct = TableSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    serialize_display! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_i128(i128);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_u128(u128);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+    }
+
+    fn serialize_str(self, value: &str) -> Result<(), Error> {
+        self.set(value)
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.set(variant)
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    serialize_unsupported! {
+        "compound values";
+        serialize_bytes(&[u8]) -> ();
+        serialize_unit() -> ();
+        serialize_unit_struct(&'static str) -> ();
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported("compound values"))
+    }
+
+    fn serialize_map(self, _len: Option<usize>) -> Result<TableSerializer<'a>, Error> {
+        self.table()
+    }
+
+    fn serialize_struct(
+        self,
+        _name: &'static str,
+        _len: usize,
+    ) -> Result<TableSerializer<'a>, Error> {
+        self.table()
+    }
+}
+
+impl<'a> FieldSerializer<'a> {
+    /// Starts serializing a section named after the field.
+    fn table(self) -> Result<TableSerializer<'a>, Error> {
+        if self.section.is_some() {
+            return Err(unsupported("nested sections"));
+        }
+        self.document
+            .check_section(self.key)
+            .map_err(|msg| ser::Error::custom(format_args!("section {:?}: {msg}", self.key)))?;
+        Ok(TableSerializer::new(
+            self.document,
+            Some(self.key.to_string()),
+        ))
+    }
+}
+
+/// Serializer for map keys.
+struct KeySerializer;
+
+impl ser::Serializer for KeySerializer {
+    type Ok = String;
+    type Error = Error;
+    type SerializeSeq = Impossible<String, Error>;
+    type SerializeTuple = Impossible<String, Error>;
+    type SerializeTupleStruct = Impossible<String, Error>;
+    type SerializeTupleVariant = Impossible<String, Error>;
+    type SerializeMap = Impossible<String, Error>;
+    type SerializeStruct = Impossible<String, Error>;
+    type SerializeStructVariant = Impossible<String, Error>;
+
+    fn serialize_str(self, value: &str) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_char(self, value: char) -> Result<String, Error> {
+        Ok(value.to_string())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        variant: &'static str,
+    ) -> Result<String, Error> {
+        Ok(variant.to_string())
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<String, Error> {
+        value.serialize(self)
+    }
+
+    serialize_unsupported! {
+        "keys other than strings";
+        serialize_bool(bool) -> String;
+        serialize_i8(i8) -> String;
+        serialize_i16(i16) -> String;
+        serialize_i32(i32) -> String;
+        serialize_i64(i64) -> String;
+        serialize_u8(u8) -> String;
+        serialize_u16(u16) -> String;
+        serialize_u32(u32) -> String;
+        serialize_u64(u64) -> String;
+        serialize_f32(f32) -> String;
+        serialize_f64(f64) -> String;
+        serialize_bytes(&[u8]) -> String;
+        serialize_none() -> String;
+        serialize_unit() -> String;
+        serialize_unit_struct(&'static str) -> String;
+        serialize_seq(Option<usize>) -> Self::SerializeSeq;
+        serialize_tuple(usize) -> Self::SerializeTuple;
+        serialize_tuple_struct(&'static str, usize) -> Self::SerializeTupleStruct;
+        serialize_tuple_variant(&'static str, u32, &'static str, usize) -> Self::SerializeTupleVariant;
+        serialize_map(Option<usize>) -> Self::SerializeMap;
+        serialize_struct(&'static str, usize) -> Self::SerializeStruct;
+        serialize_struct_variant(&'static str, u32, &'static str, usize) -> Self::SerializeStructVariant;
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _value: &T) -> Result<String, Error> {
+        Err(unsupported("keys other than strings"))
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _name: &'static str,
+        _variant_index: u32,
+        _variant: &'static str,
+        _value: &T,
+    ) -> Result<String, Error> {
+        Err(unsupported("keys other than strings"))
+    }
+}
@@ -0,0 +1,180 @@
+//! Locating items in the input.
+
+use core::ops::Range;
+
+use crate::Item;
+use crate::Newline;
+use crate::Parser;
+use crate::subrange;
+
+/// A region of the input.
+///
+/// Both the line and column are zero-based (like [`Parser::line`]), the
+/// column is counted in bytes from the start of the line.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub struct Span {
+    /// Byte offset of the start of the region
+    pub start: usize,
+    /// Byte offset of the end of the region (exclusive)
+    pub end: usize,
+    /// Line of the start of the region
+    pub line: u32,
+    /// Column of the start of the region
+    pub column: usize,
+}
+
+impl Span {
+    /// Returns the byte range of the span.
+    #[inline]
+    #[must_use]
+    pub const fn range(&self) -> Range<usize> {
+        self.start..self.end
+    }
+
+    /// Returns the length of the span in bytes.
+    #[inline]
+    #[must_use]
+    pub const fn len(&self) -> usize {
+        self.end - self.start
+    }
+
+    /// Returns true if the span is empty.
+    #[inline]
+    #[must_use]
+    pub const fn is_empty(&self) -> bool {
+        self.start == self.end
+    }
+
+    /// Returns the span of a sub-slice of the line this span covers.
+    fn sub(&self, raw: &str, inner: &str) -> Self {
+        let range = subrange(raw, inner);
+        Self {
+            start: self.start + range.start,
+            end: self.start + range.end,
+            line: self.line,
+            column: self.column + range.start,
+        }
+    }
+}
+
+/// An item together with its location in the input.
+///
+/// The span of the whole item covers the `raw` line (without the newline).
+/// [`Item::SectionEnd`] has an empty span at the position it was emitted.
+///
+/// For [`Item::Property`] the key, delimiter and value sub-spans are available
+/// as well. Key and value spans cover the trimmed key and value, if the key or
+/// value is empty the span is empty. Inline comments of properties and section
+/// headers have a sub-span too.
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let mut iter = ini::Parser::new("[SECTION]\n  Key = Value").spanned().skip(2);
+/// let spanned = iter.next().unwrap();
+/// assert_eq!(spanned.span.range(), 10..23);
+/// assert_eq!(spanned.span.line, 1);
+/// assert_eq!(spanned.span.column, 0);
+/// assert_eq!(spanned.key.map(|span| span.range()), Some(12..15));
+/// assert_eq!(spanned.delimiter.map(|span| span.column), Some(6));
+/// assert_eq!(spanned.value.map(|span| span.range()), Some(18..23));
+/// ```
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct Spanned<'a> {
+    /// The item
+    pub item: Item<'a>,
+    /// Span of the whole item
+    pub span: Span,
+    /// Newline terminating the item (see [`Parser::newline`])
+    pub newline: Option<Newline>,
+    /// Span of the key (properties only)
+    pub key: Option<Span>,
+    /// Span of the delimiter (properties with a value only)
+    pub delimiter: Option<Span>,
+    /// Span of the value (properties with a value only)
+    pub value: Option<Span>,
+    /// Span of the inline comment (if any)
+    pub comment: Option<Span>,
+}
+
+/// Iterator adapter yielding items together with their location.
+///
+/// See [`Parser::spanned`].
+#[derive(Clone, Debug)]
+pub struct SpannedItems<'a> {
+    parser: Parser<'a>,
+}
+
+impl<'a> SpannedItems<'a> {
+    #[inline]
+    pub(crate) const fn new(parser: Parser<'a>) -> Self {
+        Self { parser }
+    }
+
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &Parser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for SpannedItems<'a> {
+    type Item = Spanned<'a>;
+
+    fn next(&mut self) -> Option<Spanned<'a>> {
+        let start = self.parser.offset();
+        let line = self.parser.line();
+        let item = self.parser.next()?;
+        let raw = match item {
+            Item::Error(raw)
+            | Item::Section { raw, .. }
+            | Item::Property { raw, .. }
+            | Item::Comment { raw }
+            | Item::Blank { raw } => raw,
+            Item::SectionEnd => "",
+        };
+        let span = Span {
+            start,
+            end: start + raw.len(),
+            line,
+            column: 0,
+        };
+        let comment = match item {
+            Item::Section { comment, .. } | Item::Property { comment, .. } => {
+                comment.map(|comment| span.sub(raw, comment))
+            }
+            _ => None,
+        };
+        let (key, delimiter, value) = match item {
+            Item::Property { key, val, .. } => {
+                let key = span.sub(raw, key);
+                match val {
+                    Some(val) => {
+                        // The parser splits the line at the first delimiter
+                        // (which may be whitespace)
+                        let delimiters = self.parser.options().delimiters;
+                        let delimiter = raw
+                            .bytes()
+                            .position(|chr| delimiters.contains(&chr))
+                            .map(|offset| span.sub(raw, &raw[offset..=offset]));
+                        (Some(key), delimiter, Some(span.sub(raw, val)))
+                    }
+                    None => (Some(key), None, None),
+                }
+            }
+            _ => (None, None, None),
+        };
+        Some(Spanned {
+            item,
+            span,
+            newline: self.parser.newline(),
+            key,
+            delimiter,
+            value,
+            comment,
+        })
+    }
+}
+
+impl core::iter::FusedIterator for SpannedItems<'_> {}
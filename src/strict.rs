@@ -0,0 +1,226 @@
+//! Strict parsing with diagnostics.
+
+use core::fmt;
+use core::str;
+
+use crate::ByteParser;
+use crate::Item;
+use crate::Parser;
+use crate::SpannedItems;
+use crate::from_utf8;
+use crate::is_indent;
+
+/// The kind of a syntax error.
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+#[non_exhaustive]
+pub enum ErrorKind {
+    /// A section header without a closing `]`.
+    UnterminatedSection,
+    /// Characters after the closing `]` of a section header.
+    TrailingCharacters,
+    /// A section header with an empty name (`[]`).
+    EmptySectionName,
+    /// A property with a value but an empty key (`=value`).
+    EmptyKey,
+    /// Invalid UTF-8 (see [`ByteParser::lossy`]).
+    InvalidUtf8,
+}
+
+impl ErrorKind {
+    /// Returns a short description of the error.
+    #[inline]
+    #[must_use]
+    pub const fn as_str(self) -> &'static str {
+        match self {
+            Self::UnterminatedSection => "unterminated section header",
+            Self::TrailingCharacters => "trailing characters after section header",
+            Self::EmptySectionName => "empty section name",
+            Self::EmptyKey => "empty key",
+            Self::InvalidUtf8 => "invalid UTF-8",
+        }
+    }
+}
+
+impl fmt::Display for ErrorKind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+/// A syntax error found by [`Parser::strict`] or [`ByteParser::lossy`].
+///
+/// Both the line and column are zero-based (like [`Span`](crate::Span)), the
+/// column is counted in bytes from the start of the line. The [`Display`]
+/// implementation shows them one-based.
+///
+/// [`Display`]: fmt::Display
+#[derive(Copy, Clone, Debug, Eq, PartialEq, Hash)]
+pub struct ParseError {
+    kind: ErrorKind,
+    line: u32,
+    column: usize,
+}
+
+impl ParseError {
+    /// Returns the kind of the error.
+    #[inline]
+    #[must_use]
+    pub const fn kind(&self) -> ErrorKind {
+        self.kind
+    }
+
+    /// Returns the line of the error.
+    #[inline]
+    #[must_use]
+    pub const fn line(&self) -> u32 {
+        self.line
+    }
+
+    /// Returns the column of the error.
+    #[inline]
+    #[must_use]
+    pub const fn column(&self) -> usize {
+        self.column
+    }
+}
+
+impl fmt::Display for ParseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "line {}, column {}: {}",
+            self.line + 1,
+            self.column + 1,
+            self.kind
+        )
+    }
+}
+
+impl core::error::Error for ParseError {}
+
+/// Iterator adapter returning syntax errors as [`ParseError`]s.
+///
+/// See [`Parser::strict`].
+#[derive(Clone, Debug)]
+pub struct Strict<'a> {
+    inner: SpannedItems<'a>,
+}
+
+impl<'a> Strict<'a> {
+    #[inline]
+    pub(crate) const fn new(parser: Parser<'a>) -> Self {
+        Self {
+            inner: SpannedItems::new(parser),
+        }
+    }
+
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &Parser<'a> {
+        self.inner.parser()
+    }
+
+    /// Diagnoses a malformed section header, returning the kind and column.
+    fn diagnose_section(&self, raw: &'a str) -> (ErrorKind, usize) {
+        let (line, _) = self.parser().split_inline_comment(raw.as_bytes());
+        let line = line.trim_ascii_end();
+        let indent = line.iter().take_while(|&&chr| is_indent(chr)).count();
+        match line[indent..].iter().rposition(|&chr| chr == b']') {
+            Some(end) => {
+                let after = indent + end + 1;
+                let spaces = line[after..]
+                    .iter()
+                    .take_while(|&&chr| is_indent(chr))
+                    .count();
+                (ErrorKind::TrailingCharacters, after + spaces)
+            }
+            None => (ErrorKind::UnterminatedSection, line.len()),
+        }
+    }
+}
+
+impl<'a> Iterator for Strict<'a> {
+    type Item = Result<Item<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let spanned = self.inner.next()?;
+        let line = spanned.span.line;
+        let (kind, column) = match spanned.item {
+            Item::Error(raw) => self.diagnose_section(raw),
+            Item::Section { name: "", raw, .. } => {
+                let indent = raw.bytes().take_while(|&chr| is_indent(chr)).count();
+                (ErrorKind::EmptySectionName, indent + 1)
+            }
+            Item::Property {
+                key: "",
+                val: Some(_),
+                ..
+            } => (
+                ErrorKind::EmptyKey,
+                spanned.delimiter.map_or(0, |span| span.column),
+            ),
+            item => return Some(Ok(item)),
+        };
+        Some(Err(ParseError { kind, line, column }))
+    }
+}
+
+impl core::iter::FusedIterator for Strict<'_> {}
+
+/// Iterator adapter returning items that are not valid UTF-8 as
+/// [`ParseError`]s.
+///
+/// See [`ByteParser::lossy`].
+#[derive(Clone, Debug)]
+pub struct Lossy<'a> {
+    parser: ByteParser<'a>,
+}
+
+impl<'a> Lossy<'a> {
+    #[inline]
+    pub(crate) const fn new(parser: ByteParser<'a>) -> Self {
+        Self { parser }
+    }
+
+    /// Returns a reference to the underlying parser.
+    #[inline]
+    #[must_use]
+    pub const fn parser(&self) -> &ByteParser<'a> {
+        &self.parser
+    }
+}
+
+impl<'a> Iterator for Lossy<'a> {
+    type Item = Result<Item<'a>, ParseError>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let line = self.parser.line();
+        let item = self.parser.next()?;
+        Some(check_utf8(item, line))
+    }
+}
+
+/// Converts an item starting on `line` to a string item, or reports the first
+/// invalid byte as an error.
+pub(crate) fn check_utf8(item: Item<'_, [u8]>, line: u32) -> Result<Item<'_>, ParseError> {
+    let Some(Err(err)) = item.raw().map(str::from_utf8) else {
+        // All fields are sub-slices of the raw line, split at ascii bytes
+        return Ok(item.map(from_utf8));
+    };
+    // The raw text of continued values spans multiple lines
+    let raw = item.raw().unwrap_or_default();
+    let before = &raw[..err.valid_up_to()];
+    let newlines = before
+        .iter()
+        .enumerate()
+        .filter(|&(i, &chr)| chr == b'\n' || (chr == b'\r' && before.get(i + 1) != Some(&b'\n')));
+    let (lines, line_start) = newlines.fold((0, 0), |(count, _), (i, _)| (count + 1, i + 1));
+    Err(ParseError {
+        kind: ErrorKind::InvalidUtf8,
+        line: line + lines,
+        column: before.len() - line_start,
+    })
+}
+
+impl core::iter::FusedIterator for Lossy<'_> {}
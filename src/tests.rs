@@ -1,5 +1,8 @@
 extern crate std;
 
+use std::string::String;
+use std::string::ToString;
+
 use crate::*;
 
 #[track_caller]
@@ -26,6 +29,7 @@ fn test_eos() {
             Item::SectionEnd,
             Item::Section {
                 name: "SECTION",
+                comment: None,
                 raw: "[SECTION]",
             },
             Item::SectionEnd,
@@ -38,6 +42,7 @@ fn test_eos() {
             Item::SectionEnd,
             Item::Section {
                 name: "SECTION",
+                comment: None,
                 raw: "[SECTION]",
             },
             Item::SectionEnd,
@@ -66,6 +71,7 @@ fn test_eos() {
             Item::Property {
                 key: "Key",
                 val: Some("Value"),
+                comment: None,
                 raw: "Key=Value",
             },
             Item::SectionEnd,
@@ -78,6 +84,7 @@ fn test_eos() {
             Item::Property {
                 key: "Key",
                 val: Some("Value"),
+                comment: None,
                 raw: "Key=Value",
             },
             Item::SectionEnd,
@@ -90,6 +97,7 @@ fn test_eos() {
             Item::Property {
                 key: "Key",
                 val: Some("Value"),
+                comment: None,
                 raw: "Key=Value",
             },
             Item::SectionEnd,
@@ -102,6 +110,7 @@ fn test_eos() {
             Item::Property {
                 key: "Key",
                 val: Some("Value"),
+                comment: None,
                 raw: "Key=Value",
             },
             Item::SectionEnd,
@@ -114,6 +123,7 @@ fn test_eos() {
             Item::Property {
                 key: "Action",
                 val: None,
+                comment: None,
                 raw: "Action",
             },
             Item::SectionEnd,
@@ -126,6 +136,7 @@ fn test_eos() {
             Item::Property {
                 key: "Action",
                 val: None,
+                comment: None,
                 raw: "Action",
             },
             Item::SectionEnd,
@@ -138,6 +149,7 @@ fn test_eos() {
             Item::Property {
                 key: "Action",
                 val: None,
+                comment: None,
                 raw: "Action",
             },
             Item::SectionEnd,
@@ -150,6 +162,7 @@ fn test_eos() {
             Item::Property {
                 key: "Action",
                 val: None,
+                comment: None,
                 raw: "Action",
             },
             Item::SectionEnd,
@@ -166,6 +179,7 @@ fn test_empty() {
             Item::Property {
                 key: "Key",
                 val: None,
+                comment: None,
                 raw: "Key",
             },
             Item::SectionEnd,
@@ -178,6 +192,7 @@ fn test_empty() {
             Item::Property {
                 key: "Key",
                 val: Some(""),
+                comment: None,
                 raw: "Key=",
             },
             Item::SectionEnd,
@@ -193,16 +208,19 @@ fn test_empty_strings() {
             Item::SectionEnd,
             Item::Section {
                 name: "",
+                comment: None,
                 raw: "[]",
             },
             Item::Property {
                 key: "",
                 val: Some(""),
+                comment: None,
                 raw: "=",
             },
             Item::Property {
                 key: "",
                 val: Some(""),
+                comment: None,
                 raw: " = ",
             },
             Item::Comment { raw: ";" },
@@ -210,16 +228,19 @@ fn test_empty_strings() {
             Item::Property {
                 key: "",
                 val: Some(""),
+                comment: None,
                 raw: "= ",
             },
             Item::Property {
                 key: "",
                 val: Some(""),
+                comment: None,
                 raw: " =",
             },
             Item::Property {
                 key: "",
                 val: Some(""),
+                comment: None,
                 raw: "=",
             },
             Item::SectionEnd,
@@ -288,3 +309,1383 @@ fn test_terminates() {
     for _ in Parser::new("[") {}
     for _ in Parser::new("[] ") {}
 }
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_document_roundtrip() {
+    let s = "global = 1\n[\n[SECTION]\n;comment\n Key  =  Value \n\nAction\n[other\n[SECTION]\nKey = Duplicate\n";
+    let doc = Document::parse(s);
+    assert_eq!(doc.to_string(), s);
+    assert!(doc.items().eq(Parser::new(s)));
+    assert_eq!(doc.get(None, "global"), Some("1"));
+    assert_eq!(doc.get(Some("SECTION"), "Key"), Some("Value"));
+    assert_eq!(doc.get(Some("SECTION"), "Action"), None);
+    assert!(doc.contains_key(Some("SECTION"), "Action"));
+    assert!(!doc.contains_section(Some("other")));
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_document_edit() {
+    let mut doc = Document::parse("[A]\n Key  =  Value \nAction\n\n[B]\nx=1\n");
+    doc.set(Some("A"), "Key", "Other");
+    doc.set(Some("A"), "Action", "Run");
+    doc.set(None, "global", "yes");
+    doc.insert(Some("B"), "x", "2");
+    assert!(doc.insert_section("C"));
+    assert!(!doc.insert_section("C"));
+    doc.set(Some("C"), "y", "3");
+    assert_eq!(
+        doc.to_string(),
+        "global=yes\n[A]\n Key  =  Other \nAction=Run\n\n[B]\nx=1\nx=2\n\n[C]\ny=3\n"
+    );
+
+    assert!(doc.remove(Some("B"), "x"));
+    assert_eq!(doc.get(Some("B"), "x"), Some("2"));
+    assert!(doc.remove_section(Some("A")));
+    assert!(!doc.remove_section(Some("A")));
+    assert!(doc.remove_section(None));
+    assert!(!doc.remove(None, "global"));
+    assert_eq!(doc.to_string(), "[B]\nx=2\n\n[C]\ny=3\n");
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_document_invalid() {
+    use std::panic::AssertUnwindSafe;
+    use std::panic::catch_unwind;
+
+    let inline = ParserOptions::new().inline_comment_prefixes(b";#");
+    let backslash = ParserOptions::new().continuation(Continuation::Backslash);
+    let space = ParserOptions::new().delimiters(b" ");
+    let none = ParserOptions::new().delimiters(b"");
+    for (options, section, key, value) in [
+        // Newlines
+        (ParserOptions::new(), Some("a"), "k", "x\n[evil]\ny=1"),
+        (ParserOptions::new(), Some("a"), "k", "x\ry"),
+        (ParserOptions::new(), Some("a"), "new", "x\n"),
+        (ParserOptions::new(), Some("a"), "k\n", "y"),
+        (ParserOptions::new(), Some("b\n"), "k", "y"),
+        // Keys that change the kind of the line
+        (ParserOptions::new(), Some("a"), "k=x", "y"),
+        (ParserOptions::new(), Some("a"), "#k", "v"),
+        (ParserOptions::new(), Some("a"), ";k", "v"),
+        (ParserOptions::new(), Some("a"), "[k", "v"),
+        (space, Some("a"), "", "v"),
+        (none, Some("a"), "k", "v"),
+        // Whitespace that is trimmed when parsing
+        (ParserOptions::new(), Some("a"), " k ", "v"),
+        (ParserOptions::new(), Some("a"), "k", " v "),
+        (ParserOptions::new(), Some("a"), "k", "v\t"),
+        (ParserOptions::new(), Some(" x "), "k", "v"),
+        // Sections
+        (ParserOptions::new(), Some("b]"), "k", "y"),
+        (inline, Some("b ;c"), "k", "y"),
+        // Inline comments and continuations
+        (inline, Some("a"), "k", "x ;y"),
+        (inline, Some("a"), "k", "#y"),
+        (inline, Some("a"), "k\t#", "y"),
+        (backslash, Some("a"), "k", "x\\"),
+    ] {
+        let s = "[a]\nk = v ; c\n";
+        let mut doc = Document::parse_with_options(s, options);
+        let result = catch_unwind(AssertUnwindSafe(|| doc.set(section, key, value)));
+        assert!(result.is_err(), "{section:?} {key:?} {value:?}");
+        let result = catch_unwind(AssertUnwindSafe(|| doc.insert(section, key, value)));
+        assert!(result.is_err(), "{section:?} {key:?} {value:?}");
+        assert_eq!(doc.to_string(), s);
+    }
+    let mut doc = Document::new();
+    assert!(catch_unwind(AssertUnwindSafe(|| doc.insert_section("b]c"))).is_err());
+    assert!(catch_unwind(AssertUnwindSafe(|| doc.insert_section(" b"))).is_err());
+
+    // Everything else parses back the same
+    for (options, section, key, value) in [
+        (
+            ParserOptions::new().delimiters(b":"),
+            Some("b"),
+            "k=x",
+            "a=b; c",
+        ),
+        (ParserOptions::new(), Some("a"), "k", "[x]"),
+        (ParserOptions::new(), Some("a"), "k", "x ] y"),
+        (ParserOptions::new(), Some("a b"), "k#;[", "#x ;y"),
+        (ParserOptions::new(), Some("a"), "", ""),
+        (inline, Some("a;b"), "k;", "x;y#"),
+        (inline, Some("a"), "k", "x"),
+        (backslash, Some("a"), "k", "x\\ y"),
+        (space, Some("a"), "k=", "x y"),
+    ] {
+        let mut doc = Document::parse_with_options("[a]\nk = value ; c\n", options);
+        doc.set(section, key, value);
+        let reparsed = Document::parse_with_options(&doc.to_string(), options);
+        assert_eq!(reparsed, doc);
+        assert_eq!(reparsed.get(section, key), Some(value));
+    }
+}
+
+#[test]
+fn test_writer() {
+    let s = "global = 1\n[\n[ SECTION ]\n;comment\n Key  =  Value \n\nAction\n[other\n";
+    let mut writer = Writer::new(String::new());
+    writer.write_items(Parser::new(s)).unwrap();
+    assert_eq!(writer.into_inner(), s);
+
+    let mut writer = Writer::canonical(String::new()).newline(Newline::CrLf);
+    writer.write_items(Parser::new(s)).unwrap();
+    assert_eq!(
+        writer.into_inner(),
+        "global=1\r\n[\r\n[SECTION]\r\n;comment\r\nKey=Value\r\n\r\nAction\r\n[other\r\n"
+    );
+
+    let display: String = Parser::new(s).map(|item| item.to_string()).collect();
+    assert_eq!(
+        display,
+        "global=1\n[\n[SECTION]\n;comment\nKey=Value\n\nAction\n[other\n"
+    );
+}
+
+#[test]
+fn test_newlines() {
+    let s = "[A]\r\nk=v\rx\n\n[B]";
+    let value: std::vec::Vec<_> = Parser::new(s).with_newlines().collect();
+    assert_eq!(
+        value,
+        [
+            (Item::SectionEnd, None),
+            (
+                Item::Section {
+                    name: "A",
+                    comment: None,
+                    raw: "[A]",
+                },
+                Some(Newline::CrLf)
+            ),
+            (
+                Item::Property {
+                    key: "k",
+                    val: Some("v"),
+                    comment: None,
+                    raw: "k=v",
+                },
+                Some(Newline::Cr)
+            ),
+            (
+                Item::Property {
+                    key: "x",
+                    val: None,
+                    comment: None,
+                    raw: "x",
+                },
+                Some(Newline::Lf)
+            ),
+            (Item::Blank { raw: "" }, Some(Newline::Lf)),
+            (Item::SectionEnd, None),
+            (
+                Item::Section {
+                    name: "B",
+                    comment: None,
+                    raw: "[B]",
+                },
+                None
+            ),
+            (Item::SectionEnd, None),
+        ]
+    );
+
+    let mut writer = Writer::new(String::new());
+    writer.write_lines(Parser::new(s).with_newlines()).unwrap();
+    assert_eq!(writer.into_inner(), s);
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_document_newlines() {
+    let s = "[A]\r\nk=v\rx\n\n[B]";
+    let mut doc = Document::parse(s);
+    assert_eq!(doc.to_string(), s);
+    assert_eq!(doc.newline(), Newline::CrLf);
+    doc.set(Some("B"), "y", "1");
+    assert_eq!(doc.to_string(), "[A]\r\nk=v\rx\n\n[B]\r\ny=1");
+    doc.remove(Some("B"), "y");
+    assert_eq!(doc.to_string(), s);
+}
+
+#[test]
+fn test_spans() {
+    let s = "g\r\n[ A ]\n k = v \n =\n;c";
+    let spans: std::vec::Vec<_> = Parser::new(s)
+        .spanned()
+        .map(|spanned| {
+            (
+                &s[spanned.span.range()],
+                spanned.span.line,
+                spanned.key.map(|span| (&s[span.range()], span.column)),
+                spanned
+                    .delimiter
+                    .map(|span| (&s[span.range()], span.column)),
+                spanned.value.map(|span| (&s[span.range()], span.column)),
+            )
+        })
+        .collect();
+    assert_eq!(
+        spans,
+        [
+            ("g", 0, Some(("g", 0)), None, None),
+            ("", 1, None, None, None),
+            ("[ A ]", 1, None, None, None),
+            (" k = v ", 2, Some(("k", 1)), Some(("=", 3)), Some(("v", 5))),
+            (" =", 3, Some(("", 0)), Some(("=", 1)), Some(("", 2))),
+            (";c", 4, None, None, None),
+            ("", 4, None, None, None),
+        ]
+    );
+    let spanned = Parser::new(s).spanned().nth(2).unwrap();
+    assert_eq!(spanned.span.start, 3);
+    assert_eq!(spanned.newline, Some(Newline::Lf));
+
+    // Whitespace delimiters
+    let options = ParserOptions::new().delimiters(b" :");
+    let s = "key val
+k  v
+x : y";
+    let delimiters: std::vec::Vec<_> = Parser::with_options(s, options)
+        .spanned()
+        .filter_map(|spanned| spanned.delimiter)
+        .map(|span| (span.range(), span.column))
+        .collect();
+    assert_eq!(delimiters, [(3..4, 3), (9..10, 1), (14..15, 1)]);
+}
+
+#[test]
+fn test_options() {
+    const OPTIONS: ParserOptions = ParserOptions::new()
+        .comment_prefixes(b";")
+        .delimiters(b":=")
+        .indented_sections(true)
+        .indented_comments(true)
+        .require_section_end(false);
+    let value: std::vec::Vec<_> =
+        Parser::with_options("#k=v\n\t; c\n [ A ] x\n[B\nk : v=w\n  \n", OPTIONS).collect();
+    assert_eq!(
+        value,
+        [
+            Item::Property {
+                key: "#k",
+                val: Some("v"),
+                comment: None,
+                raw: "#k=v",
+            },
+            Item::Comment { raw: "\t; c" },
+            Item::SectionEnd,
+            Item::Section {
+                name: "A",
+                comment: None,
+                raw: " [ A ] x",
+            },
+            Item::SectionEnd,
+            Item::Section {
+                name: "B",
+                comment: None,
+                raw: "[B",
+            },
+            Item::Property {
+                key: "k",
+                val: Some("v=w"),
+                comment: None,
+                raw: "k : v=w",
+            },
+            Item::Blank { raw: "  " },
+            Item::SectionEnd,
+        ]
+    );
+
+    // Indentation is not allowed by default
+    check(
+        " [A]\n ;c",
+        &[
+            Item::Property {
+                key: "[A]",
+                val: None,
+                comment: None,
+                raw: " [A]",
+            },
+            Item::Property {
+                key: ";c",
+                val: None,
+                comment: None,
+                raw: " ;c",
+            },
+            Item::SectionEnd,
+        ],
+    );
+
+    // No delimiters means no values
+    let value: std::vec::Vec<_> =
+        Parser::with_options("k=v", ParserOptions::new().delimiters(b"")).collect();
+    assert_eq!(
+        value,
+        [
+            Item::Property {
+                key: "k=v",
+                val: None,
+                comment: None,
+                raw: "k=v",
+            },
+            Item::SectionEnd,
+        ]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_document_options() {
+    let options = ParserOptions::new().delimiters(b":");
+    let mut doc = Document::parse_with_options("[A]\nk: v\nx\n", options);
+    assert_eq!(doc.get(Some("A"), "k"), Some("v"));
+    doc.set(Some("A"), "x", "1");
+    doc.set(Some("A"), "y", "2");
+    assert_eq!(doc.to_string(), "[A]\nk: v\nx:1\ny:2\n");
+}
+
+#[test]
+fn test_inline_comments() {
+    let options = ParserOptions::new().inline_comment_prefixes(b";#");
+    let value: std::vec::Vec<_> = Parser::with_options(
+        "[A] ; c\n[B];x\nk = v ; c\nk=v;x\nflag #c\n  ; only\nurl = a#b # c  ",
+        options,
+    )
+    .collect();
+    assert_eq!(
+        value,
+        [
+            Item::SectionEnd,
+            Item::Section {
+                name: "A",
+                comment: Some("; c"),
+                raw: "[A] ; c",
+            },
+            Item::SectionEnd,
+            Item::Error("[B];x"),
+            Item::Property {
+                key: "k",
+                val: Some("v"),
+                comment: Some("; c"),
+                raw: "k = v ; c",
+            },
+            Item::Property {
+                key: "k",
+                val: Some("v;x"),
+                comment: None,
+                raw: "k=v;x",
+            },
+            Item::Property {
+                key: "flag",
+                val: None,
+                comment: Some("#c"),
+                raw: "flag #c",
+            },
+            Item::Comment { raw: "  ; only" },
+            Item::Property {
+                key: "url",
+                val: Some("a#b"),
+                comment: Some("# c"),
+                raw: "url = a#b # c  ",
+            },
+            Item::SectionEnd,
+        ]
+    );
+
+    let spanned = Parser::with_options("k = v ; c", options)
+        .spanned()
+        .next()
+        .unwrap();
+    assert_eq!(spanned.value.map(|span| span.range()), Some(4..5));
+    assert_eq!(spanned.comment.map(|span| span.range()), Some(6..9));
+
+    let mut writer = Writer::canonical(String::new());
+    writer
+        .write_items(Parser::with_options("[ A ]  ;c\n k = v  #c", options))
+        .unwrap();
+    assert_eq!(writer.into_inner(), "[A] ;c\nk=v #c\n");
+}
+
+#[test]
+fn test_writer_options() {
+    let options = ParserOptions::new().delimiters(b":");
+    let s = "[A]\nk: a=b\n  x :y\nflag\n";
+    let mut writer = Writer::canonical(String::new()).options(options);
+    writer
+        .write_items(Parser::with_options(s, options))
+        .unwrap();
+    let canonical = writer.into_inner();
+    assert_eq!(canonical, "[A]\nk:a=b\nx:y\nflag\n");
+
+    // The canonical output parses back to the same properties
+    let properties = |s| {
+        Parser::with_options(s, options)
+            .filter_map(|item| match item {
+                Item::Property { key, val, .. } => Some((key, val)),
+                _ => None,
+            })
+            .collect::<std::vec::Vec<_>>()
+    };
+    assert_eq!(properties(&canonical), properties(s));
+    assert_eq!(
+        properties(s),
+        [("k", Some("a=b")), ("x", Some("y")), ("flag", None)]
+    );
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_document_inline_comments() {
+    let options = ParserOptions::new().inline_comment_prefixes(b";");
+    let mut doc = Document::parse_with_options("[A] ; c\nk = v ; c\nflag ; c\n", options);
+    assert_eq!(doc.get(Some("A"), "k"), Some("v"));
+    doc.set(Some("A"), "k", "longer");
+    doc.set(Some("A"), "flag", "1");
+    assert_eq!(doc.to_string(), "[A] ; c\nk = longer ; c\nflag=1 ; c\n");
+    assert!(doc.items().any(|item| matches!(
+        item,
+        Item::Property {
+            key: "flag",
+            val: Some("1"),
+            comment: Some("; c"),
+            ..
+        }
+    )));
+}
+
+#[test]
+fn test_continuation() {
+    let options = ParserOptions::new().continuation(Continuation::Indented);
+    let s = "a = 1\n  2\r\n\t3\nb = x\n\n  y\nflag\n  z\n[s]\nc=\n [t]\n";
+    let mut parser = Parser::with_options(s, options).with_newlines();
+    assert_eq!(
+        parser.next(),
+        Some((
+            Item::Property {
+                key: "a",
+                val: Some("1\n  2\r\n\t3"),
+                comment: None,
+                raw: "a = 1\n  2\r\n\t3",
+            },
+            Some(Newline::Lf)
+        ))
+    );
+    assert_eq!(parser.parser().line(), 3);
+    let value: std::vec::Vec<_> = parser.map(|(item, _)| item).collect();
+    assert_eq!(
+        value,
+        [
+            Item::Property {
+                key: "b",
+                val: Some("x"),
+                comment: None,
+                raw: "b = x",
+            },
+            Item::Blank { raw: "" },
+            Item::Property {
+                key: "y",
+                val: None,
+                comment: None,
+                raw: "  y",
+            },
+            Item::Property {
+                key: "flag",
+                val: None,
+                comment: None,
+                raw: "flag",
+            },
+            Item::Property {
+                key: "z",
+                val: None,
+                comment: None,
+                raw: "  z",
+            },
+            Item::SectionEnd,
+            Item::Section {
+                name: "s",
+                comment: None,
+                raw: "[s]",
+            },
+            Item::Property {
+                key: "c",
+                val: Some("[t]"),
+                comment: None,
+                raw: "c=\n [t]",
+            },
+            Item::SectionEnd,
+        ]
+    );
+    let lines: std::vec::Vec<_> = Continuation::Indented.lines("1\n  2\r\n\t3").collect();
+    assert_eq!(lines, ["1", "2", "3"]);
+
+    let options = ParserOptions::new()
+        .continuation(Continuation::Backslash)
+        .inline_comment_prefixes(b";");
+    let s = "a = 1 \\\n2\\\r\n 3\nb = x \\ ; c\ny\nc = \\";
+    let value: std::vec::Vec<_> = Parser::with_options(s, options).collect();
+    assert_eq!(
+        value,
+        [
+            Item::Property {
+                key: "a",
+                val: Some("1 \\\n2\\\r\n 3"),
+                comment: None,
+                raw: "a = 1 \\\n2\\\r\n 3",
+            },
+            Item::Property {
+                key: "b",
+                val: Some("x \\"),
+                comment: Some("; c"),
+                raw: "b = x \\ ; c",
+            },
+            Item::Property {
+                key: "y",
+                val: None,
+                comment: None,
+                raw: "y",
+            },
+            Item::Property {
+                key: "c",
+                val: Some("\\"),
+                comment: None,
+                raw: "c = \\",
+            },
+            Item::SectionEnd,
+        ]
+    );
+    let lines: std::vec::Vec<_> = Continuation::Backslash.lines("1 \\\n2\\\r\n 3").collect();
+    assert_eq!(lines, ["1", "2", "3"]);
+
+    let mut writer = Writer::new(String::new());
+    writer
+        .write_lines(Parser::with_options(s, options).with_newlines())
+        .unwrap();
+    assert_eq!(writer.into_inner(), s);
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Config<'a> {
+        name: &'a str,
+        count: Option<u32>,
+        server: Server,
+        #[serde(default)]
+        missing: BTreeMap<String, String>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Server {
+        host: String,
+        port: u16,
+        ratio: f32,
+        verbose: bool,
+        mode: Mode,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    let s = "; leading comment\nname = example\n\n[server]\nhost = localhost\nport = 8080\nratio = 0.5\nverbose\nmode = Slow\n[ignored]\nx = y\n";
+    let config: Config<'_> = de::from_str(s).unwrap();
+    assert_eq!(
+        config,
+        Config {
+            name: "example",
+            count: None,
+            server: Server {
+                host: "localhost".to_string(),
+                port: 8080,
+                ratio: 0.5,
+                verbose: true,
+                mode: Mode::Slow,
+            },
+            missing: BTreeMap::new(),
+        }
+    );
+
+    let map: BTreeMap<&str, BTreeMap<&str, &str>> = de::from_str("[a]\nx = 1\n[b]\ny = 2").unwrap();
+    assert_eq!(map["a"]["x"], "1");
+    assert_eq!(map["b"]["y"], "2");
+
+    let options = ParserOptions::new().delimiters(b":");
+    let map: BTreeMap<&str, &str> = de::from_str_with_options("a: 1\nb: 2", options).unwrap();
+    assert_eq!(map["b"], "2");
+
+    let err = de::from_str::<Config<'_>>("name = x\n[server]\nport = big\n").unwrap_err();
+    assert_eq!(err.line(), Some(2));
+    assert!(err.to_string().starts_with("line 3: "));
+
+    let err = de::from_str::<Config<'_>>("name = x\n\n[server\n").unwrap_err();
+    assert_eq!(err.line(), Some(2));
+
+    // Errors of a section are reported on its header
+    let err = de::from_str::<Config<'_>>("name = x\n[server]\nhost = x\n").unwrap_err();
+    assert_eq!(err.line(), Some(1));
+
+    let err = de::from_str::<Config<'_>>("[other]\n").unwrap_err();
+    assert_eq!(err.line(), None);
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Optional {
+        a: Option<Section>,
+        b: Option<Section>,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize)]
+    struct Section {
+        x: u32,
+    }
+
+    let optional: Optional = de::from_str("[a]\nx = 1\n").unwrap();
+    assert_eq!(
+        optional,
+        Optional {
+            a: Some(Section { x: 1 }),
+            b: None,
+        }
+    );
+    let err = de::from_str::<Optional>("[a]\nx = 1\n[b]\n").unwrap_err();
+    assert_eq!(err.line(), Some(2));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serialize() {
+    use std::collections::BTreeMap;
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct Config {
+        name: String,
+        count: Option<u32>,
+        server: Server,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    struct Server {
+        host: String,
+        verbose: bool,
+        mode: Mode,
+    }
+
+    #[derive(Debug, PartialEq, serde::Deserialize, serde::Serialize)]
+    enum Mode {
+        Fast,
+        Slow,
+    }
+
+    let s = "; comment\nname =  example\n\n[server]\nhost=localhost\r\nverbose\nunknown = 1\nmode = Fast\n\n[other]\nkey = value";
+    let mut doc = Document::parse(s);
+    let mut config: Config = de::from_str(s).unwrap();
+
+    // Serializing the unchanged value leaves the document untouched
+    ser::to_document(&mut doc, &config).unwrap();
+    assert_eq!(doc.to_string(), s);
+
+    config.server.mode = Mode::Slow;
+    config.count = Some(3);
+    config.name = "changed".to_string();
+    ser::to_document(&mut doc, &config).unwrap();
+    assert_eq!(
+        doc.to_string(),
+        "; comment\nname =  changed\ncount=3\n\n[server]\nhost=localhost\r\nverbose\nunknown = 1\nmode = Slow\n\n[other]\nkey = value"
+    );
+    assert_eq!(de::from_str::<Config>(&doc.to_string()).unwrap(), config);
+
+    let mut map = BTreeMap::new();
+    map.insert("new", BTreeMap::from([("a", 1)]));
+    ser::to_document(&mut doc, &map).unwrap();
+    assert!(doc.to_string().ends_with("key = value\n\n[new]\na=1"));
+
+    let mut doc = Document::new();
+    assert!(ser::to_document(&mut doc, &1).is_err());
+    assert!(ser::to_document(&mut doc, &BTreeMap::from([("a", [1, 2])])).is_err());
+    assert!(
+        ser::to_document(
+            &mut doc,
+            &BTreeMap::from([("a", BTreeMap::from([("b", BTreeMap::from([("c", 1)]))]))])
+        )
+        .is_err()
+    );
+    assert!(ser::to_document(&mut doc, &BTreeMap::from([(1, 1)])).is_err());
+
+    // Anything that would not parse back the same is rejected
+    let inline = ParserOptions::new().inline_comment_prefixes(b";");
+    for (options, section, key, value) in [
+        (ParserOptions::new(), "s", "x", "a\nb"),
+        (ParserOptions::new(), "s", "x", "\r"),
+        (ParserOptions::new(), "s", "x=y", "a"),
+        (ParserOptions::new(), "s", "#k", "v"),
+        (ParserOptions::new(), "s", "[x", "1"),
+        (ParserOptions::new(), "s", " k ", "v"),
+        (ParserOptions::new(), "s", "k", " v "),
+        (ParserOptions::new(), "a]", "k", "v"),
+        (ParserOptions::new(), " s ", "k", "v"),
+        (inline, "s", "k", "x ;y"),
+    ] {
+        let s = "[s]\na=1\n";
+        let mut doc = Document::parse_with_options(s, options);
+        let value = BTreeMap::from([(section, BTreeMap::from([(key, value)]))]);
+        assert!(ser::to_document(&mut doc, &value).is_err(), "{value:?}");
+        assert_eq!(doc.to_string(), s);
+    }
+
+    // Everything else parses back to the same value
+    for options in [ParserOptions::new(), inline] {
+        let mut doc = Document::parse_with_options("[s]\na=1\n", options);
+        let value = BTreeMap::from([
+            (
+                "s",
+                BTreeMap::from([("a", "a = b ] c"), ("k#", "x;y"), ("k[", "#v")]),
+            ),
+            ("t u", BTreeMap::from([("", "")])),
+        ]);
+        ser::to_document(&mut doc, &value).unwrap();
+        let s = doc.to_string();
+        assert_eq!(
+            de::from_str_with_options::<BTreeMap<&str, BTreeMap<&str, &str>>>(&s, options).unwrap(),
+            value
+        );
+        // Serializing again leaves the document untouched
+        ser::to_document(&mut doc, &value).unwrap();
+        assert_eq!(doc.to_string(), s);
+    }
+}
+
+#[test]
+fn test_strict() {
+    #[track_caller]
+    fn check_strict(s: &str, options: ParserOptions, kind: ErrorKind, line: u32, column: usize) {
+        let err = Parser::with_options(s, options)
+            .strict()
+            .find_map(Result::err)
+            .unwrap();
+        assert_eq!(err.kind(), kind);
+        assert_eq!((err.line(), err.column()), (line, column));
+    }
+
+    let options = ParserOptions::new();
+    check_strict("[Section", options, ErrorKind::UnterminatedSection, 0, 8);
+    check_strict(
+        "a=b\n[Section  \n",
+        options,
+        ErrorKind::UnterminatedSection,
+        1,
+        8,
+    );
+    check_strict("[Section] x", options, ErrorKind::TrailingCharacters, 0, 10);
+    check_strict(
+        "\n\n[a]]  x]y",
+        options,
+        ErrorKind::TrailingCharacters,
+        2,
+        8,
+    );
+    check_strict("[ ]", options, ErrorKind::EmptySectionName, 0, 1);
+    check_strict("[a]\n  = value", options, ErrorKind::EmptyKey, 1, 2);
+
+    let options = ParserOptions::new()
+        .indented_sections(true)
+        .inline_comment_prefixes(b";");
+    check_strict("  [a] x ; c", options, ErrorKind::TrailingCharacters, 0, 6);
+    check_strict("  [a ; c]", options, ErrorKind::UnterminatedSection, 0, 4);
+    check_strict("  [] ; c", options, ErrorKind::EmptySectionName, 0, 3);
+
+    let s = "[a]\nkey\n; comment\nx = y";
+    let items: std::vec::Vec<_> = Parser::new(s).strict().collect::<Result<_, _>>().unwrap();
+    assert_eq!(items, Parser::new(s).collect::<std::vec::Vec<_>>());
+
+    let err = Parser::new("[a] x").strict().find_map(Result::err).unwrap();
+    assert_eq!(
+        err.to_string(),
+        "line 1, column 5: trailing characters after section header"
+    );
+}
+
+/// Documents and dialects the streaming parsers are checked against.
+fn streaming_fixtures() -> impl Iterator<Item = (&'static [u8], ParserOptions)> {
+    const DOCUMENTS: [&[u8]; 10] = [
+        b"",
+        b"\n",
+        b"[Section]\nKey = Value",
+        "; comment\r\n\r\n[Säction]\r\nKëy = Välue ☃\r\n[Empty]\r[Other]\rx\r".as_bytes(),
+        b"Key = first\n  second\n\n  third\n[A]\nb = x \\\n  y\\\nz\n  [B] ; c\n  # d\n",
+        b"[Error\nkey\n=value\n  [  ] ; x\n",
+        // Invalid UTF-8
+        b"a=1\nb=2\n\xff\n[c]\n",
+        b"a = 1\n  \xe9 \\\nb\n",
+        b"[S]\r\na = x \\\r\n y\xc0 ; z\r\n",
+        b"a = \xe2\x98",
+    ];
+    const OPTIONS: [ParserOptions; 3] = [
+        ParserOptions::new(),
+        ParserOptions::new()
+            .continuation(Continuation::Indented)
+            .indented_sections(true)
+            .indented_comments(true)
+            .inline_comment_prefixes(b";"),
+        ParserOptions::new().continuation(Continuation::Backslash),
+    ];
+    DOCUMENTS
+        .into_iter()
+        .flat_map(|s| OPTIONS.map(|options| (s, options)))
+}
+
+/// Returns the items (with their newline) the streaming parsers must return
+/// for a fixture, up to and including the first invalid UTF-8 error.
+fn streaming_expected(
+    s: &[u8],
+    options: ParserOptions,
+) -> std::vec::Vec<Result<(Item<'_>, Option<Newline>), ParseError>> {
+    let mut lossy = ByteParser::with_options(s, options).lossy();
+    let mut expected = std::vec::Vec::new();
+    while let Some(item) = lossy.next() {
+        let error = item.is_err();
+        expected.push(item.map(|item| (item, lossy.parser().newline())));
+        if error {
+            break;
+        }
+    }
+    expected
+}
+
+#[cfg(feature = "std")]
+#[test]
+fn test_reader() {
+    use std::io::BufReader;
+    use std::vec::Vec;
+
+    #[track_caller]
+    fn check_reader(s: &[u8], options: ParserOptions) {
+        let expected = streaming_expected(s, options);
+        for capacity in [1, 2, 3, 7, 64] {
+            let mut parser =
+                ReaderParser::with_options(BufReader::with_capacity(capacity, s), options);
+            let mut index = 0;
+            while let Some(item) = parser.next() {
+                match (item, &expected[index]) {
+                    (Ok(item), Ok((expected_item, newline))) => {
+                        assert_eq!(item.to_string(), expected_item.to_string());
+                        assert_eq!(std::format!("{item:?}"), std::format!("{expected_item:?}"));
+                        assert_eq!(parser.newline(), *newline);
+                    }
+                    (Err(err), Err(expected_err)) => {
+                        let err = err.into_inner().unwrap().downcast::<ParseError>().unwrap();
+                        assert_eq!(*err, *expected_err);
+                    }
+                    (item, expected_item) => panic!("{item:?} != {expected_item:?}"),
+                }
+                index += 1;
+            }
+            assert_eq!(index, expected.len());
+            assert!(parser.next().is_none());
+        }
+    }
+
+    for (s, options) in streaming_fixtures() {
+        check_reader(s, options);
+    }
+
+    // A long line is not parsed again after every read
+    let s = std::format!("k = {}\nx\n", "v".repeat(1 << 20));
+    let mut parser = ReaderParser::new(BufReader::with_capacity(16, s.as_bytes()));
+    assert!(matches!(
+        parser.next(),
+        Some(Ok(Item::Property { val: Some(val), .. })) if val.len() == 1 << 20
+    ));
+    assert!(matches!(
+        parser.next(),
+        Some(Ok(Item::Property { key: "x", .. }))
+    ));
+    assert!(matches!(parser.next(), Some(Ok(Item::SectionEnd))));
+    assert!(parser.next().is_none());
+
+    // Items before invalid UTF-8 are returned, the error has its location in
+    // the document
+    #[track_caller]
+    fn check_invalid(s: &[u8], keys: &[&str], line: u32, column: usize) {
+        for capacity in [1, 2, 3, 7, 64] {
+            let mut parser = ReaderParser::with_options(
+                BufReader::with_capacity(capacity, s),
+                ParserOptions::new().continuation(Continuation::Backslash),
+            );
+            let mut found = Vec::new();
+            let err = loop {
+                match parser.next().unwrap() {
+                    Ok(Item::Property { key, .. }) => found.push(key.to_string()),
+                    Ok(_) => {}
+                    Err(err) => break err,
+                }
+            };
+            assert_eq!(found, keys);
+            assert_eq!(err.kind(), std::io::ErrorKind::InvalidData);
+            let err = err.into_inner().unwrap().downcast::<ParseError>().unwrap();
+            assert_eq!(err.kind(), ErrorKind::InvalidUtf8);
+            assert_eq!((err.line(), err.column()), (line, column));
+            assert!(parser.next().is_none());
+        }
+    }
+
+    check_invalid(b"a = b\nc = \xff\n", &["a"], 1, 4);
+    check_invalid(b"a=1\nb=2\n\xff\n", &["a", "b"], 2, 0);
+    check_invalid(b"a=1\nb=2\n\xff", &["a", "b"], 2, 0);
+    check_invalid(b"a=1\nb=2\\\n\xff\nc\n", &["a"], 2, 0);
+    check_invalid(b"a = \xe2\x98", &[], 0, 4);
+    check_invalid(b"a = \xe2\x98\n", &[], 0, 4);
+}
+
+#[test]
+fn test_push() {
+    use std::vec::Vec;
+
+    #[track_caller]
+    fn check_push(s: &[u8], options: ParserOptions) {
+        let expected = streaming_expected(s, options);
+        let expected_result = match expected.last() {
+            Some(Err(_)) => Err(PushError::InvalidUtf8),
+            _ => Ok(()),
+        };
+        let expected: Vec<_> = expected
+            .into_iter()
+            .filter_map(Result::ok)
+            .map(|(item, newline)| (std::format!("{item:?}"), newline))
+            .collect();
+        for chunk_size in [1, 2, 3, 7, 64] {
+            let mut buf = [0; 64];
+            let mut parser = PushParser::with_options(&mut buf, options);
+            let mut items = Vec::new();
+            let mut on_item =
+                |item: Item<'_>, newline| items.push((std::format!("{item:?}"), newline));
+            let result = s
+                .chunks(chunk_size)
+                .try_for_each(|chunk| parser.push(chunk, &mut on_item))
+                .and_then(|()| parser.finish(&mut on_item));
+            assert_eq!(result, expected_result);
+            assert_eq!(items, expected);
+        }
+    }
+
+    for (s, options) in streaming_fixtures() {
+        check_push(s, options);
+    }
+
+    let mut buf = [0; 8];
+    let mut parser = PushParser::new(&mut buf);
+    let mut count = 0;
+    assert_eq!(
+        parser.push(b"a\nb\nc = too long\n", |_, _| count += 1),
+        Err(PushError::BufferFull)
+    );
+    assert_eq!(count, 1);
+
+    let mut buf = [0; 16];
+    let mut parser = PushParser::new(&mut buf);
+    let mut keys = Vec::new();
+    let result = parser.push(b"a = b\nc = \xff\n", |item, _| {
+        if let Item::Property { key, .. } = item {
+            keys.push(key.to_string());
+        }
+    });
+    assert_eq!(result, Err(PushError::InvalidUtf8));
+    assert_eq!(keys, ["a"]);
+
+    let mut buf = [0; 16];
+    let mut parser = PushParser::new(&mut buf);
+    parser.push(b"a = \xe2\x98", |_, _| {}).unwrap();
+    assert_eq!(parser.finish(|_, _| {}), Err(PushError::InvalidUtf8));
+}
+
+#[test]
+fn test_byte_parser() {
+    use std::vec::Vec;
+
+    let s = "; c\r\n[ Säction ]\r\nKëy = Välue\n  =\n[Error\nx ☃";
+    let options = ParserOptions::new().continuation(Continuation::Indented);
+    let items: Vec<_> = ByteParser::with_options(s.as_bytes(), options)
+        .map(|item| item.map(from_utf8))
+        .collect();
+    let expected: Vec<_> = Parser::with_options(s, options).collect();
+    assert_eq!(items, expected);
+    let lossy: Vec<_> = ByteParser::with_options(s.as_bytes(), options)
+        .lossy()
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(lossy, expected);
+
+    let s = b"[Caf\xe9]\r\nKey = \xff\r\nOk = 1\rx = a\n  b\xfe";
+    let mut parser = ByteParser::with_options(s, options);
+    assert_eq!(
+        parser.nth(1),
+        Some(Item::Section {
+            name: &b"Caf\xe9"[..],
+            comment: None,
+            raw: &b"[Caf\xe9]"[..]
+        })
+    );
+    assert_eq!(parser.newline(), Some(Newline::CrLf));
+    assert_eq!(parser.remainder(), &s[8..]);
+
+    let items: Vec<_> = ByteParser::with_options(s, options)
+        .lossy()
+        .map(|item| item.map_err(|err| (err.kind(), err.line(), err.column())))
+        .collect();
+    assert_eq!(
+        items,
+        [
+            Ok(Item::SectionEnd),
+            Err((ErrorKind::InvalidUtf8, 0, 4)),
+            Err((ErrorKind::InvalidUtf8, 1, 6)),
+            Ok(Item::Property {
+                key: "Ok",
+                val: Some("1"),
+                comment: None,
+                raw: "Ok = 1"
+            }),
+            Err((ErrorKind::InvalidUtf8, 4, 3)),
+            Ok(Item::SectionEnd),
+        ]
+    );
+}
+
+#[test]
+fn test_utf8_boundaries() {
+    use std::vec::Vec;
+
+    /// Returns the offsets where the parser split the input.
+    fn splits(s: &[u8], options: ParserOptions) -> Vec<usize> {
+        let splits = core::cell::RefCell::new(Vec::new());
+        for item in ByteParser::with_options(s, options) {
+            let _ = item.map(|field: &[u8]| {
+                let start = field.as_ptr() as usize - s.as_ptr() as usize;
+                splits.borrow_mut().extend([start, start + field.len()]);
+                field
+            });
+        }
+        splits.into_inner()
+    }
+
+    let options = [
+        ParserOptions::new(),
+        ParserOptions::new()
+            .delimiters(b"=: ")
+            .inline_comment_prefixes(b";#")
+            .indented_sections(true)
+            .indented_comments(true)
+            .continuation(Continuation::Backslash),
+        ParserOptions::new()
+            .require_section_end(false)
+            .continuation(Continuation::Indented),
+    ];
+    for chr in ["é", "☃", "\u{10348}", "\u{a0}", "\u{2028}"] {
+        let s = "[c] ;c\n[c]c\nc=c\nc :c #c\n;c\n#c\nc = c\\\n c\n c\n[ c ]";
+        let s = s.replace('c', chr);
+        for options in options {
+            // Parser converts every field to a `&str`, which is checked in
+            // debug builds
+            assert!(Parser::with_options(&s, options).count() > 1);
+            for offset in splits(s.as_bytes(), options) {
+                assert!(s.is_char_boundary(offset), "{s:?} {offset}");
+            }
+        }
+    }
+
+    // Invalid UTF-8 is split at ascii bytes as well
+    let s = b"[\x80] ;\xff\n\xe2[\x80]\xe2\n\x80=\x80\n\xff :\xff #\xff\n;\xe2\n\xe2 = \x80\\\n \xff\n\x80";
+    for options in options {
+        for offset in splits(s, options) {
+            let before = offset.checked_sub(1).map(|i| s[i]);
+            let after = s.get(offset).copied();
+            assert!(
+                before.is_none_or(|b| b.is_ascii()) || after.is_none_or(|b| b.is_ascii()),
+                "{offset}"
+            );
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_encoding() {
+    use std::vec::Vec;
+
+    let decoded = Decoded::decode(b"\xEF\xBB\xBF[S]\r\nk = v").unwrap();
+    assert_eq!(
+        (decoded.encoding(), decoded.has_bom(), decoded.text()),
+        (Encoding::Utf8, true, "[S]\r\nk = v")
+    );
+    assert_eq!(
+        decoded.parser().nth(1),
+        Some(Item::Section {
+            name: "S",
+            comment: None,
+            raw: "[S]"
+        })
+    );
+    assert_eq!(
+        decoded.encode(decoded.text()).unwrap(),
+        b"\xEF\xBB\xBF[S]\r\nk = v"
+    );
+
+    let decoded = Decoded::decode(b"k = v").unwrap();
+    assert_eq!(
+        (decoded.encoding(), decoded.has_bom()),
+        (Encoding::Utf8, false)
+    );
+    assert!(matches!(
+        decoded.into_text(),
+        std::borrow::Cow::Borrowed("k = v")
+    ));
+
+    let text = "[Säction]\nKey = 😀 ☃\n";
+    for encoding in [Encoding::Utf16Le, Encoding::Utf16Be] {
+        let mut bytes = encoding.bom().to_vec();
+        bytes.extend(encoding.encode(text).unwrap());
+        let decoded = Decoded::decode(&bytes).unwrap();
+        assert_eq!((decoded.encoding(), decoded.text()), (encoding, text));
+        assert_eq!(decoded.encode(text).unwrap(), bytes);
+    }
+    assert_eq!(
+        Decoded::decode(b"\xFF\xFEa\0b"),
+        Err(EncodingError::Invalid { offset: 4 })
+    );
+    assert_eq!(
+        Decoded::decode(b"\xFE\xFF\0a\xD8\x00\0b"),
+        Err(EncodingError::Invalid { offset: 4 })
+    );
+
+    let bytes: Vec<u8> = (0..=255).collect();
+    let text = Encoding::Windows1252.decode(&bytes).unwrap();
+    assert_eq!(text.chars().nth(0x80), Some('€'));
+    assert_eq!(text.chars().nth(0xE9), Some('é'));
+    assert_eq!(Encoding::Windows1252.encode(&text).unwrap(), bytes);
+    assert_eq!(
+        Encoding::Windows1252.encode("a☃"),
+        Err(EncodingError::Unmappable { offset: 1 })
+    );
+
+    assert_eq!(
+        Decoded::decode(b"a\xE9"),
+        Err(EncodingError::Invalid { offset: 1 })
+    );
+    let decoded = Decoded::decode_with_fallback(b"k = Caf\xE9", Encoding::Windows1252).unwrap();
+    assert_eq!(
+        (decoded.encoding(), decoded.text()),
+        (Encoding::Windows1252, "k = Café")
+    );
+    assert_eq!(decoded.encode("k = Thé").unwrap(), b"k = Th\xE9");
+}
+
+#[test]
+fn test_sections() {
+    use std::vec::Vec;
+
+    let s = "; c\nglobal = 1\n[a]\nkey = x\n\n[Error\nkey = y\n[b]\nflag\nkey = z";
+    let sections: Vec<_> = Parser::new(s).sections().collect();
+    let headers: Vec<_> = sections.iter().map(Section::header).collect();
+    assert_eq!(
+        headers,
+        [
+            None,
+            Some(Item::Section {
+                name: "a",
+                comment: None,
+                raw: "[a]"
+            }),
+            Some(Item::Error("[Error")),
+            Some(Item::Section {
+                name: "b",
+                comment: None,
+                raw: "[b]"
+            }),
+        ]
+    );
+    let names: Vec<_> = sections.iter().map(Section::name).collect();
+    assert_eq!(names, [None, Some("a"), None, Some("b")]);
+    assert_eq!(sections[0].raw(), None);
+    assert_eq!(sections[0].get("global"), Some("1"));
+    assert_eq!(sections[0].items().count(), 2);
+    assert_eq!(sections[1].get("key"), Some("x"));
+    assert_eq!(sections[1].line(), 3);
+    assert_eq!(sections[2].get("key"), Some("y"));
+    assert_eq!(sections[3].get("flag"), None);
+    let properties: Vec<_> = sections[3].properties().collect();
+    assert_eq!(properties, [("flag", None), ("key", Some("z"))]);
+
+    // The items of all sections are the items of the parser
+    let items: Vec<_> = Parser::new(s)
+        .sections()
+        .flat_map(|section| section.header().into_iter().chain(section.items()))
+        .collect();
+    let expected: Vec<_> = Parser::new(s)
+        .filter(|item| *item != Item::SectionEnd)
+        .collect();
+    assert_eq!(items, expected);
+
+    let sections: Vec<_> = Parser::new("").sections().collect();
+    assert_eq!(sections.len(), 1);
+    assert_eq!(sections[0].items().count(), 0);
+    let sections: Vec<_> = Parser::new("[a]").sections().collect();
+    assert_eq!(sections.len(), 2);
+    assert_eq!(sections[0].items().count(), 0);
+}
+
+#[test]
+fn test_with_section() {
+    use std::vec::Vec;
+
+    let s = "k = 0\n[a]\nk = 1\n\n[Error\nk = 2\n[b]\nk = 3";
+    let items: Vec<_> = Parser::new(s)
+        .with_section()
+        .map(|(section, item)| (section, item.raw()))
+        .collect();
+    assert_eq!(
+        items,
+        [
+            (None, Some("k = 0")),
+            (None, None),
+            (Some("a"), Some("[a]")),
+            (Some("a"), Some("k = 1")),
+            (Some("a"), Some("")),
+            (Some("a"), None),
+            (None, Some("[Error")),
+            (None, Some("k = 2")),
+            (None, None),
+            (Some("b"), Some("[b]")),
+            (Some("b"), Some("k = 3")),
+            (Some("b"), None),
+        ]
+    );
+
+    let mut iter = Parser::new("[a]\nk = 1").with_section();
+    assert_eq!(iter.section(), None);
+    iter.nth(1);
+    assert_eq!(iter.section(), Some("a"));
+    assert_eq!(iter.parser().line(), 1);
+}
+
+#[test]
+fn test_find() {
+    let s = "k = 0\r\n[a]\r\nk = 1\r\n\r\n[Error\r\nk = 2\r\n[b]\r\nflag\r\n[a]\r\nk = 3\r\nj = 4\r\n[c]";
+    assert_eq!(find(s, None, "k"), Some("0"));
+    assert_eq!(find(s, Some("a"), "k"), Some("1"));
+    assert_eq!(find(s, Some("a"), "j"), Some("4"));
+    assert_eq!(find(s, Some("b"), "flag"), None);
+    assert_eq!(find(s, Some("b"), "k"), None);
+    assert_eq!(find(s, Some("Error"), "k"), None);
+    assert_eq!(find(s, Some("c"), "k"), None);
+    assert_eq!(find(s, Some("d"), "k"), None);
+    assert_eq!(find("", None, "k"), None);
+    assert_eq!(find("[a]\nk", Some("a"), "k"), None);
+    // Lines starting with `[` inside a property are not headers
+    assert_eq!(
+        find("k = [a]\n [a]\nk=1\n[a]\nk=2", Some("a"), "k"),
+        Some("2")
+    );
+
+    let keys = [
+        (Some("a"), "j"),
+        (None, "k"),
+        (Some("b"), "k"),
+        (Some("a"), "k"),
+    ];
+    assert_eq!(find_many(s, &keys), [Some("4"), Some("0"), None, Some("1")]);
+    assert_eq!(find_many(s, &[]), [None; 0]);
+
+    // Matches the owned document model
+    #[cfg(feature = "alloc")]
+    {
+        let doc = Document::parse(s);
+        for (section, key) in keys {
+            assert_eq!(find(s, section, key), doc.get(section, key));
+        }
+    }
+}
+
+#[cfg(feature = "alloc")]
+#[test]
+fn test_index() {
+    use std::vec::Vec;
+
+    let s = "k = 0\r\nflag\r\n[b]\r\nk = 1\r\n\r\n[Error\r\nk = 2\r\n[a]\r\nk = 3\r\n[b]\r\nk = 4\r\nj = 5\r\n[c]";
+    let index = Index::parse(s);
+    let doc = Document::parse(s);
+    assert_eq!(index.source(), s);
+    assert_eq!(index.sections().collect::<Vec<_>>(), ["b", "a", "b", "c"]);
+    assert_eq!(
+        index.sections().collect::<Vec<_>>(),
+        doc.sections().collect::<Vec<_>>()
+    );
+    for section in [
+        None,
+        Some("a"),
+        Some("b"),
+        Some("c"),
+        Some("d"),
+        Some("Error"),
+    ] {
+        assert_eq!(
+            index.contains_section(section),
+            doc.contains_section(section)
+        );
+        for key in ["k", "j", "flag", "x"] {
+            assert_eq!(index.get(section, key), doc.get(section, key));
+            assert_eq!(
+                index.contains_key(section, key),
+                doc.contains_key(section, key)
+            );
+        }
+    }
+    assert_eq!(index.section_offset(None), Some(0));
+    assert_eq!(index.section_offset(Some("b")), Some(13));
+    assert_eq!(index.section_offset(Some("d")), None);
+    assert_eq!(index.property_offset(Some("b"), "k"), Some(18));
+    assert_eq!(index.property_offset(Some("b"), "j"), s.find("j ="));
+    assert_eq!(index.property_offset(Some("Error"), "k"), None);
+
+    let options = ParserOptions::new().delimiters(b":");
+    let index = Index::parse_with_options("[a]\nk: v", options);
+    assert_eq!(index.get(Some("a"), "k"), Some("v"));
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_par() {
+    use std::vec::Vec;
+
+    #[track_caller]
+    fn check_par(s: &str, options: ParserOptions) {
+        let items: Vec<_> = Parser::with_options(s, options).collect();
+        let spanned: Vec<_> = Parser::with_options(s, options).spanned().collect();
+        for chunk_size in [0, 1, 2, 5, 16, s.len()] {
+            assert_eq!(par::parse_chunks(s, options, chunk_size), items);
+            assert_eq!(par::spanned_chunks(s, options, chunk_size), spanned);
+        }
+        assert_eq!(par::parse(s, options), items);
+        assert_eq!(par::spanned(s, options), spanned);
+    }
+
+    let s = "g = 1\r\n; c\r\n\r\n[a]\r\nk = v ; x\r\n  indented\r\n[Error\r\nk\r\n[b]\nk = a\\\n[c]\n\rk = Välue";
+    for options in [
+        ParserOptions::new(),
+        ParserOptions::new().continuation(Continuation::Indented),
+        ParserOptions::new().continuation(Continuation::Backslash),
+        ParserOptions::new()
+            .inline_comment_prefixes(b";")
+            .indented_sections(true),
+    ] {
+        check_par(s, options);
+    }
+    check_par("", ParserOptions::new());
+    check_par("\n\n\n", ParserOptions::new());
+    check_par("[a]", ParserOptions::new());
+}
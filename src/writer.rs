@@ -0,0 +1,418 @@
+//! Writing items back out.
+
+use core::fmt;
+
+use crate::Item;
+use crate::Newline;
+use crate::ParserOptions;
+
+/// How a [`Writer`] formats items.
+#[derive(Copy, Clone, Debug, Default, Eq, PartialEq, Hash)]
+pub enum Mode {
+    /// Write the raw lines, reproducing the original formatting.
+    #[default]
+    Preserve,
+    /// Write normalized lines: `[name]`, `key=value` and `key`.
+    ///
+    /// Properties are written with the first delimiter of the dialect (see
+    /// [`Writer::options`]), `=` by default.
+    ///
+    /// Inline comments are separated by a single space. Comments and syntax
+    /// errors are written as is, blank lines are written as empty lines.
+    Canonical,
+}
+
+/// Returns the delimiter written between keys and values in canonical mode.
+const fn canonical_delimiter(options: &ParserOptions) -> char {
+    match options.delimiters {
+        [chr, ..] => *chr as char,
+        [] => '=',
+    }
+}
+
+/// Writes the canonical form of an item, without a newline.
+pub(crate) fn write_canonical<W: fmt::Write + ?Sized>(
+    out: &mut W,
+    item: &Item<'_>,
+    delimiter: char,
+) -> fmt::Result {
+    let comment = match *item {
+        Item::Error(error) => return out.write_str(error),
+        Item::Section { name, comment, .. } => {
+            write!(out, "[{name}]")?;
+            comment
+        }
+        Item::SectionEnd | Item::Blank { .. } => return Ok(()),
+        Item::Property {
+            key, val, comment, ..
+        } => {
+            out.write_str(key)?;
+            if let Some(value) = val {
+                write!(out, "{delimiter}{value}")?;
+            }
+            comment
+        }
+        Item::Comment { raw: comment } => return out.write_str(comment),
+    };
+    match comment {
+        Some(comment) => write!(out, " {comment}"),
+        None => Ok(()),
+    }
+}
+
+/// Writes the raw form of an item, without a newline.
+fn write_raw<W: fmt::Write + ?Sized>(out: &mut W, item: &Item<'_>) -> fmt::Result {
+    match *item {
+        Item::Error(raw)
+        | Item::Section { raw, .. }
+        | Item::Property { raw, .. }
+        | Item::Comment { raw }
+        | Item::Blank { raw } => out.write_str(raw),
+        Item::SectionEnd => Ok(()),
+    }
+}
+
+/// Ini writer.
+///
+/// Writes a stream of [`Item`]s to a [`fmt::Write`]. [`Item::SectionEnd`] is
+/// not written at all.
+///
+/// In the default [`Mode::Preserve`] mode, the `raw` line of each item is
+/// written. Together with the newlines from [`Parser::with_newlines`] this
+/// reproduces the original document exactly:
+///
+/// ```
+/// use ini_roundtrip as ini;
+///
+/// let document = "[ SECTION ]\r\n;this is a comment\n  Key = Value  ";
+///
+/// let mut writer = ini::Writer::new(String::new());
+/// writer.write_lines(ini::Parser::new(document).with_newlines()).unwrap();
+/// assert_eq!(writer.into_inner(), document);
+/// ```
+///
+/// When writing items without newline information (using
+/// [`write_item`](Writer::write_item)), every line is terminated by the
+/// configured newline ([`Newline::Lf`] by default).
+///
+/// In [`Mode::Canonical`] mode, items are written in a normalized form:
+///
+/// ```
+/// # use ini_roundtrip as ini;
+/// # let document = "[ SECTION ]\r\n;this is a comment\n  Key = Value  ";
+/// let mut writer = ini::Writer::canonical(String::new());
+/// writer.write_items(ini::Parser::new(document)).unwrap();
+/// assert_eq!(writer.into_inner(), "[SECTION]\n;this is a comment\nKey=Value\n");
+/// ```
+///
+/// [`Parser::with_newlines`]: crate::Parser::with_newlines
+#[derive(Clone, Debug)]
+pub struct Writer<W> {
+    out: W,
+    mode: Mode,
+    newline: Newline,
+    delimiter: char,
+}
+
+impl<W> Writer<W> {
+    /// Constructs a new `Writer` that preserves formatting.
+    #[inline]
+    #[must_use]
+    pub const fn new(out: W) -> Self {
+        Self::with_mode(out, Mode::Preserve)
+    }
+
+    /// Constructs a new `Writer` that writes normalized output.
+    #[inline]
+    #[must_use]
+    pub const fn canonical(out: W) -> Self {
+        Self::with_mode(out, Mode::Canonical)
+    }
+
+    /// Constructs a new `Writer` using the given mode.
+    #[inline]
+    #[must_use]
+    pub const fn with_mode(out: W, mode: Mode) -> Self {
+        Self {
+            out,
+            mode,
+            newline: Newline::Lf,
+            delimiter: canonical_delimiter(&ParserOptions::new()),
+        }
+    }
+
+    /// Sets the newline used by [`write_item`](Writer::write_item) (default
+    /// [`Newline::Lf`]).
+    #[inline]
+    #[must_use]
+    pub const fn newline(mut self, newline: Newline) -> Self {
+        self.newline = newline;
+        self
+    }
+
+    /// Sets the dialect of the output (default [`ParserOptions::new`]).
+    ///
+    /// In [`Mode::Canonical`] mode, properties are written with the first of
+    /// the configured delimiters (or `=` if there are none), so the output
+    /// parses back to the same items with these options:
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let options = ini::ParserOptions::new().delimiters(b":");
+    /// let mut writer = ini::Writer::canonical(String::new()).options(options);
+    /// writer.write_items(ini::Parser::with_options("key : a=b", options)).unwrap();
+    /// assert_eq!(writer.into_inner(), "key:a=b\n");
+    /// ```
+    #[inline]
+    #[must_use]
+    pub const fn options(mut self, options: ParserOptions) -> Self {
+        self.delimiter = canonical_delimiter(&options);
+        self
+    }
+
+    /// Returns the mode of the writer.
+    #[inline]
+    #[must_use]
+    pub const fn mode(&self) -> Mode {
+        self.mode
+    }
+
+    /// Returns a reference to the underlying writer.
+    #[inline]
+    #[must_use]
+    pub const fn get_ref(&self) -> &W {
+        &self.out
+    }
+
+    /// Returns a mutable reference to the underlying writer.
+    #[inline]
+    #[must_use]
+    pub const fn get_mut(&mut self) -> &mut W {
+        &mut self.out
+    }
+
+    /// Consumes the writer, returning the underlying writer.
+    #[inline]
+    #[must_use]
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+impl<W: fmt::Write> Writer<W> {
+    /// Writes a single item, followed by the configured newline.
+    #[inline]
+    pub fn write_item(&mut self, item: &Item<'_>) -> fmt::Result {
+        self.write_line(item, Some(self.newline))
+    }
+
+    /// Writes a single item, followed by the given newline (if any).
+    pub fn write_line(&mut self, item: &Item<'_>, newline: Option<Newline>) -> fmt::Result {
+        if *item == Item::SectionEnd {
+            return Ok(());
+        }
+        match self.mode {
+            Mode::Preserve => write_raw(&mut self.out, item)?,
+            Mode::Canonical => write_canonical(&mut self.out, item, self.delimiter)?,
+        }
+        match newline {
+            Some(newline) => self.out.write_str(newline.as_str()),
+            None => Ok(()),
+        }
+    }
+
+    /// Writes all items from an iterator.
+    pub fn write_items<'a, I>(&mut self, items: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = Item<'a>>,
+    {
+        items
+            .into_iter()
+            .try_for_each(|item| self.write_item(&item))
+    }
+
+    /// Writes all items and their newlines from an iterator.
+    ///
+    /// This is intended to be used with [`Parser::with_newlines`].
+    ///
+    /// [`Parser::with_newlines`]: crate::Parser::with_newlines
+    pub fn write_lines<'a, I>(&mut self, lines: I) -> fmt::Result
+    where
+        I: IntoIterator<Item = (Item<'a>, Option<Newline>)>,
+    {
+        lines
+            .into_iter()
+            .try_for_each(|(item, newline)| self.write_line(&item, newline))
+    }
+}
+
+#[cfg(feature = "std")]
+pub use self::io::IoWriter;
+
+#[cfg(feature = "std")]
+mod io {
+    use core::fmt;
+    use std::io;
+
+    use super::Mode;
+    use super::Writer;
+    use crate::Item;
+    use crate::Newline;
+    use crate::ParserOptions;
+
+    /// Adapts an [`io::Write`] to [`fmt::Write`], keeping the I/O error.
+    #[derive(Debug)]
+    struct Adapter<W> {
+        inner: W,
+        error: Option<io::Error>,
+    }
+
+    impl<W: io::Write> fmt::Write for Adapter<W> {
+        fn write_str(&mut self, s: &str) -> fmt::Result {
+            self.inner.write_all(s.as_bytes()).map_err(|err| {
+                self.error = Some(err);
+                fmt::Error
+            })
+        }
+    }
+
+    /// Ini writer for [`io::Write`].
+    ///
+    /// This is the [`std::io`] equivalent of [`Writer`], see that type for
+    /// more information. Requires the `std` feature.
+    ///
+    /// ```
+    /// use ini_roundtrip as ini;
+    ///
+    /// let document = "[SECTION]\r\nKey = Value\n";
+    /// let mut writer = ini::IoWriter::new(Vec::new());
+    /// writer.write_lines(ini::Parser::new(document).with_newlines()).unwrap();
+    /// assert_eq!(writer.into_inner(), document.as_bytes());
+    /// ```
+    #[derive(Debug)]
+    pub struct IoWriter<W> {
+        writer: Writer<Adapter<W>>,
+    }
+
+    impl<W> IoWriter<W> {
+        /// Constructs a new `IoWriter` that preserves formatting.
+        #[inline]
+        #[must_use]
+        pub const fn new(out: W) -> Self {
+            Self::with_mode(out, Mode::Preserve)
+        }
+
+        /// Constructs a new `IoWriter` that writes normalized output.
+        #[inline]
+        #[must_use]
+        pub const fn canonical(out: W) -> Self {
+            Self::with_mode(out, Mode::Canonical)
+        }
+
+        /// Constructs a new `IoWriter` using the given mode.
+        #[inline]
+        #[must_use]
+        pub const fn with_mode(out: W, mode: Mode) -> Self {
+            Self {
+                writer: Writer::with_mode(
+                    Adapter {
+                        inner: out,
+                        error: None,
+                    },
+                    mode,
+                ),
+            }
+        }
+
+        /// Sets the newline used by [`write_item`](IoWriter::write_item)
+        /// (default [`Newline::Lf`]).
+        #[inline]
+        #[must_use]
+        pub fn newline(self, newline: Newline) -> Self {
+            Self {
+                writer: self.writer.newline(newline),
+            }
+        }
+
+        /// Sets the dialect of the output (see [`Writer::options`]).
+        #[inline]
+        #[must_use]
+        pub fn options(self, options: ParserOptions) -> Self {
+            Self {
+                writer: self.writer.options(options),
+            }
+        }
+
+        /// Returns the mode of the writer.
+        #[inline]
+        #[must_use]
+        pub const fn mode(&self) -> Mode {
+            self.writer.mode()
+        }
+
+        /// Returns a reference to the underlying writer.
+        #[inline]
+        #[must_use]
+        pub const fn get_ref(&self) -> &W {
+            &self.writer.get_ref().inner
+        }
+
+        /// Returns a mutable reference to the underlying writer.
+        #[inline]
+        #[must_use]
+        pub const fn get_mut(&mut self) -> &mut W {
+            &mut self.writer.get_mut().inner
+        }
+
+        /// Consumes the writer, returning the underlying writer.
+        #[inline]
+        #[must_use]
+        pub fn into_inner(self) -> W {
+            self.writer.into_inner().inner
+        }
+    }
+
+    impl<W: io::Write> IoWriter<W> {
+        /// Writes a single item, followed by the configured newline.
+        pub fn write_item(&mut self, item: &Item<'_>) -> io::Result<()> {
+            let result = self.writer.write_item(item);
+            self.check(result)
+        }
+
+        /// Writes a single item, followed by the given newline (if any).
+        pub fn write_line(&mut self, item: &Item<'_>, newline: Option<Newline>) -> io::Result<()> {
+            let result = self.writer.write_line(item, newline);
+            self.check(result)
+        }
+
+        /// Writes all items from an iterator.
+        pub fn write_items<'a, I>(&mut self, items: I) -> io::Result<()>
+        where
+            I: IntoIterator<Item = Item<'a>>,
+        {
+            let result = self.writer.write_items(items);
+            self.check(result)
+        }
+
+        /// Writes all items and their newlines from an iterator.
+        pub fn write_lines<'a, I>(&mut self, lines: I) -> io::Result<()>
+        where
+            I: IntoIterator<Item = (Item<'a>, Option<Newline>)>,
+        {
+            let result = self.writer.write_lines(lines);
+            self.check(result)
+        }
+
+        /// Converts a formatting result into the underlying I/O result.
+        fn check(&mut self, result: fmt::Result) -> io::Result<()> {
+            result.map_err(|fmt::Error| {
+                self.writer
+                    .get_mut()
+                    .error
+                    .take()
+                    .unwrap_or_else(|| io::Error::other("formatter error"))
+            })
+        }
+    }
+}